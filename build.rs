@@ -1,8 +1,7 @@
-use winresource::WindowsResource;
-
 fn main() -> std::io::Result<()> {
-    if cfg!(target_os = "windows") {
-        let mut res = WindowsResource::new();
+    #[cfg(target_os = "windows")]
+    {
+        let mut res = winresource::WindowsResource::new();
 
         res.set_icon("icon.ico")
             .set_version_info(winresource::VersionInfo::PRODUCTVERSION, 0x0001000000000000)
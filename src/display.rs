@@ -0,0 +1,185 @@
+use std::error::Error;
+use std::sync::mpsc;
+use std::thread;
+
+use winit::event_loop::EventLoopProxy;
+
+use super::OverlayEvent;
+
+#[cfg(target_os = "windows")]
+use std::cell::RefCell;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::w,
+    Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        UI::WindowsAndMessaging::{
+            CreateWindowExW,
+            DefWindowProcW,
+            DispatchMessageW,
+            GetMessageW,
+            PostQuitMessage,
+            RegisterClassW,
+            MSG,
+            WINDOW_EX_STYLE,
+            WINDOW_STYLE,
+            WM_DISPLAYCHANGE,
+            WNDCLASSW,
+        },
+    },
+};
+
+#[cfg(target_os = "linux")]
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        randr::{ConnectionExt, NotifyMask},
+        Event,
+    },
+};
+
+
+#[cfg(target_os = "windows")]
+thread_local! {
+    /// Where the window procedure of the listening window sends the changes to.
+    static PROXY: RefCell<Option<EventLoopProxy<OverlayEvent>>> = const { RefCell::new(None) };
+}
+
+
+/// Sends [`OverlayEvent::DisplayChanged`] to the event loop every time monitors are added,
+/// removed, rearranged or have their resolution changed. Changes are listened to on their own
+/// thread, for as long as the event loop runs.
+///
+/// An error is returned if the system can't tell when the monitors change.
+pub fn listen_display_changes(proxy: EventLoopProxy<OverlayEvent>) -> Result<(), Box<dyn Error>> {
+    let (ready, listening) = mpsc::channel();
+
+    thread::Builder::new()
+        .name(String::from("display"))
+        .spawn(move || run(proxy, ready))?;
+
+    listening.recv()??;
+
+    Ok(())
+}
+
+/// Creates a hidden window to be told about display changes, which the system only broadcasts to
+/// top-level windows, reports whether it exists and then waits for them.
+#[cfg(target_os = "windows")]
+fn run(proxy: EventLoopProxy<OverlayEvent>, ready: mpsc::Sender<Result<(), String>>) {
+    PROXY.with(|p| *p.borrow_mut() = Some(proxy));
+
+    let class = WNDCLASSW {
+        lpfnWndProc: Some(window_proc),
+        lpszClassName: w!("CenteredWindowsDisplayListener"),
+        ..Default::default()
+    };
+
+    if unsafe { RegisterClassW(&class) } == 0 {
+        let e = windows::core::Error::from_win32();
+        let _ = ready.send(Err(format!("Could not register the window class: {e}")));
+        return;
+    }
+
+    // The window is never shown, it only receives messages.
+    let window = unsafe {
+        CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class.lpszClassName,
+            w!(""),
+            WINDOW_STYLE::default(),
+            0, 0, 0, 0,
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+
+    if let Err(e) = window {
+        let _ = ready.send(Err(format!("Could not create the listening window: {e}")));
+        return;
+    }
+
+    let _ = ready.send(Ok(()));
+
+    let mut message = MSG::default();
+
+    while unsafe { GetMessageW(&mut message, None, 0, 0) }.as_bool() {
+        unsafe { DispatchMessageW(&message) };
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn window_proc(window: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if message == WM_DISPLAYCHANGE {
+        let sent = PROXY.with(|p| {
+            p.borrow()
+                .as_ref()
+                .is_some_and(|proxy| proxy.send_event(OverlayEvent::DisplayChanged).is_ok())
+        });
+
+        // The event loop is gone, so there's no one left to tell.
+        if !sent {
+            PostQuitMessage(0);
+        }
+    }
+
+    DefWindowProcW(window, message, wparam, lparam)
+}
+
+/// Asks the X server for the RandR notifications of the root window, reports whether they can be
+/// listened to and then waits for them.
+#[cfg(target_os = "linux")]
+fn run(proxy: EventLoopProxy<OverlayEvent>, ready: mpsc::Sender<Result<(), String>>) {
+    let (connection, screen) = match x11rb::connect(None) {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = ready.send(Err(format!("Could not connect to the X server: {e}")));
+            return;
+        }
+    };
+
+    let root = connection.setup().roots[screen].root;
+    let mask = NotifyMask::SCREEN_CHANGE | NotifyMask::CRTC_CHANGE | NotifyMask::OUTPUT_CHANGE;
+
+    // The extension has to be told which version is understood before anything else is asked.
+    let selected = connection
+        .randr_query_version(1, 2)
+        .map_err(|e| e.to_string())
+        .and_then(|c| c.reply().map_err(|e| e.to_string()))
+        .and_then(|_| connection.randr_select_input(root, mask).map_err(|e| e.to_string()))
+        .and_then(|c| c.check().map_err(|e| e.to_string()));
+
+    if let Err(e) = selected {
+        let _ = ready.send(Err(format!("Could not listen to RandR notifications: {e}")));
+        return;
+    }
+
+    let _ = ready.send(Ok(()));
+
+    loop {
+        let event = match connection.wait_for_event() {
+            Ok(e) => e,
+            Err(e) => {
+                log::error!("Lost the connection to the X server, display changes aren't followed anymore: {}", e);
+                break;
+            }
+        };
+
+        if !matches!(event, Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_)) {
+            continue;
+        }
+
+        // The event loop is gone, so there's no one left to tell.
+        if proxy.send_event(OverlayEvent::DisplayChanged).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn run(_proxy: EventLoopProxy<OverlayEvent>, ready: mpsc::Sender<Result<(), String>>) {
+    let _ = ready.send(Err(String::from("Display changes can't be listened to on this platform")));
+}
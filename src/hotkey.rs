@@ -0,0 +1,640 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+
+use serde::Deserialize;
+use winit::event_loop::EventLoopProxy;
+
+use super::{
+    find_active_window,
+    find_window_under_cursor,
+    get_windows,
+    OverlayEvent,
+    WindowInfo,
+    WindowMove,
+};
+
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::{
+    Input::KeyboardAndMouse::{
+        HOT_KEY_MODIFIERS,
+        MOD_ALT,
+        MOD_CONTROL,
+        MOD_NOREPEAT,
+        MOD_SHIFT,
+        MOD_WIN,
+        RegisterHotKey,
+        VIRTUAL_KEY,
+        VK_BACK,
+        VK_DELETE,
+        VK_DOWN,
+        VK_END,
+        VK_ESCAPE,
+        VK_F1,
+        VK_HOME,
+        VK_INSERT,
+        VK_LEFT,
+        VK_NEXT,
+        VK_PRIOR,
+        VK_RETURN,
+        VK_RIGHT,
+        VK_SPACE,
+        VK_TAB,
+        VK_UP,
+    },
+    WindowsAndMessaging::{GetMessageW, MSG, WM_HOTKEY},
+};
+
+#[cfg(target_os = "linux")]
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xproto::{ConnectionExt, GrabMode, ModMask},
+        Event,
+    },
+};
+
+
+/// The keys that can be written by name in a hotkey, alongside every name they go by.
+const NAMED_KEYS: &[(&str, HotkeyKey)] = &[
+    ("space", HotkeyKey::Space),
+    ("enter", HotkeyKey::Enter),
+    ("return", HotkeyKey::Enter),
+    ("tab", HotkeyKey::Tab),
+    ("escape", HotkeyKey::Escape),
+    ("esc", HotkeyKey::Escape),
+    ("backspace", HotkeyKey::Backspace),
+    ("insert", HotkeyKey::Insert),
+    ("ins", HotkeyKey::Insert),
+    ("delete", HotkeyKey::Delete),
+    ("del", HotkeyKey::Delete),
+    ("home", HotkeyKey::Home),
+    ("end", HotkeyKey::End),
+    ("pageup", HotkeyKey::PageUp),
+    ("pgup", HotkeyKey::PageUp),
+    ("pagedown", HotkeyKey::PageDown),
+    ("pgdn", HotkeyKey::PageDown),
+    ("left", HotkeyKey::Left),
+    ("right", HotkeyKey::Right),
+    ("up", HotkeyKey::Up),
+    ("down", HotkeyKey::Down),
+];
+
+
+/**
+A key combination that works anywhere on the desktop, such as `Ctrl+Alt+C`.
+
+Hotkeys are written in the preferences as any number of modifiers (`Ctrl`, `Alt`, `Shift` and
+`Super`, also known as `Win`) followed by a key, joined by `+`. Keys are letters, digits, function
+keys (`F1` to `F24`) or named keys such as `Space`, `Enter` or `PageUp`. Names aren't case
+sensitive, and every hotkey needs a modifier unless its key is a function key.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub super_key: bool,
+    pub key: HotkeyKey,
+}
+
+/// The key pressed alongside the modifiers of a [`Hotkey`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HotkeyKey {
+    /// An uppercase letter, from A to Z.
+    Letter(char),
+    Digit(char),
+    /// A function key, from F1 to F24.
+    Function(u8),
+    Space,
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Hotkey {
+    fn has_modifiers(&self) -> bool {
+        self.ctrl || self.alt || self.shift || self.super_key
+    }
+
+    fn parse_key(name: &str) -> Option<HotkeyKey> {
+        let mut chars = name.chars();
+
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return match c {
+                'a'..='z' => Some(HotkeyKey::Letter(c.to_ascii_uppercase())),
+                '0'..='9' => Some(HotkeyKey::Digit(c)),
+                _ => None,
+            };
+        }
+
+        if let Some(number) = name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            return (1..=24).contains(&number).then_some(HotkeyKey::Function(number));
+        }
+
+        NAMED_KEYS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, key)| *key)
+    }
+
+    /// Returns the modifiers of the hotkey, as registered with the system.
+    #[cfg(target_os = "windows")]
+    fn modifiers(&self) -> HOT_KEY_MODIFIERS {
+        // Holding the keys down shouldn't repeat the action.
+        let mut modifiers = MOD_NOREPEAT;
+
+        for (enabled, modifier) in [
+            (self.ctrl, MOD_CONTROL),
+            (self.alt, MOD_ALT),
+            (self.shift, MOD_SHIFT),
+            (self.super_key, MOD_WIN),
+        ] {
+            if enabled {
+                modifiers |= modifier;
+            }
+        }
+
+        modifiers
+    }
+
+    /// Returns the virtual-key code of the hotkey's key.
+    #[cfg(target_os = "windows")]
+    fn virtual_key(&self) -> VIRTUAL_KEY {
+        match self.key {
+            // Letters and digits share their codes with their ASCII values.
+            HotkeyKey::Letter(c) | HotkeyKey::Digit(c) => VIRTUAL_KEY(c as u16),
+            HotkeyKey::Function(number) => VIRTUAL_KEY(VK_F1.0 + number as u16 - 1),
+            HotkeyKey::Space => VK_SPACE,
+            HotkeyKey::Enter => VK_RETURN,
+            HotkeyKey::Tab => VK_TAB,
+            HotkeyKey::Escape => VK_ESCAPE,
+            HotkeyKey::Backspace => VK_BACK,
+            HotkeyKey::Insert => VK_INSERT,
+            HotkeyKey::Delete => VK_DELETE,
+            HotkeyKey::Home => VK_HOME,
+            HotkeyKey::End => VK_END,
+            HotkeyKey::PageUp => VK_PRIOR,
+            HotkeyKey::PageDown => VK_NEXT,
+            HotkeyKey::Left => VK_LEFT,
+            HotkeyKey::Right => VK_RIGHT,
+            HotkeyKey::Up => VK_UP,
+            HotkeyKey::Down => VK_DOWN,
+        }
+    }
+
+    /// Returns the modifiers of the hotkey, as X11 modifier masks.
+    #[cfg(target_os = "linux")]
+    fn modifiers(&self) -> ModMask {
+        let mut modifiers = ModMask::from(0u16);
+
+        for (enabled, modifier) in [
+            (self.ctrl, ModMask::CONTROL),
+            (self.alt, ModMask::M1),
+            (self.shift, ModMask::SHIFT),
+            (self.super_key, ModMask::M4),
+        ] {
+            if enabled {
+                modifiers |= modifier;
+            }
+        }
+
+        modifiers
+    }
+
+    /// Returns the X11 keysym of the hotkey's key.
+    #[cfg(target_os = "linux")]
+    fn keysym(&self) -> u32 {
+        match self.key {
+            // Letters and digits share their keysyms with their (lowercase) ASCII values.
+            HotkeyKey::Letter(c) => c.to_ascii_lowercase() as u32,
+            HotkeyKey::Digit(c) => c as u32,
+            HotkeyKey::Function(number) => 0xFFBE + number as u32 - 1,
+            HotkeyKey::Space => 0x0020,
+            HotkeyKey::Enter => 0xFF0D,
+            HotkeyKey::Tab => 0xFF09,
+            HotkeyKey::Escape => 0xFF1B,
+            HotkeyKey::Backspace => 0xFF08,
+            HotkeyKey::Insert => 0xFF63,
+            HotkeyKey::Delete => 0xFFFF,
+            HotkeyKey::Home => 0xFF50,
+            HotkeyKey::End => 0xFF57,
+            HotkeyKey::PageUp => 0xFF55,
+            HotkeyKey::PageDown => 0xFF56,
+            HotkeyKey::Left => 0xFF51,
+            HotkeyKey::Right => 0xFF53,
+            HotkeyKey::Up => 0xFF52,
+            HotkeyKey::Down => 0xFF54,
+        }
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (enabled, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.super_key, "Super"),
+        ] {
+            if enabled {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match self.key {
+            HotkeyKey::Letter(c) | HotkeyKey::Digit(c) => write!(f, "{c}"),
+            HotkeyKey::Function(number) => write!(f, "F{number}"),
+            key => write!(f, "{key:?}"),
+        }
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim().to_lowercase();
+        let mut parts = value.split('+').map(str::trim).collect::<Vec<_>>();
+
+        let key = parts.pop().filter(|k| !k.is_empty());
+        let key = key
+            .and_then(Self::parse_key)
+            .ok_or_else(|| format!("\"{s}\" doesn't end with a valid key"))?;
+
+        let mut hotkey = Self { ctrl: false, alt: false, shift: false, super_key: false, key };
+
+        for modifier in parts {
+            let enabled = match modifier {
+                "ctrl" | "control" => &mut hotkey.ctrl,
+                "alt" => &mut hotkey.alt,
+                "shift" => &mut hotkey.shift,
+                "super" | "win" | "meta" => &mut hotkey.super_key,
+                _ => return Err(format!("\"{modifier}\" in \"{s}\" is not a modifier")),
+            };
+
+            if *enabled {
+                return Err(format!("\"{modifier}\" is repeated in \"{s}\""));
+            }
+
+            *enabled = true;
+        }
+
+        // Without a modifier, the key couldn't be typed anymore.
+        if !hotkey.has_modifiers() && !matches!(hotkey.key, HotkeyKey::Function(_)) {
+            return Err(format!("\"{s}\" needs a modifier, such as Ctrl or Alt"));
+        }
+
+        Ok(hotkey)
+    }
+}
+
+impl TryFrom<String> for Hotkey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+
+/// What is done when a hotkey is pressed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Opens the overlay, or closes it if it's already open.
+    ShowOverlay,
+    CenterActive,
+    CenterUnderCursor,
+    CenterAll,
+}
+
+impl HotkeyAction {
+    /// Centers the windows the action applies to. Showing the overlay is up to the caller, so
+    /// nothing is done for it.
+    ///
+    /// Returns every window that was moved. Windows that fail to move are logged and skipped, so
+    /// the rest are still centered.
+    pub fn center(self) -> Result<Vec<WindowMove>, Box<dyn Error>> {
+        let mut windows = get_windows()?;
+
+        let targets = match self {
+            Self::ShowOverlay => Vec::new(),
+            Self::CenterActive => find_active_window(&mut windows).into_iter().collect(),
+            Self::CenterUnderCursor => find_window_under_cursor(&mut windows).into_iter().collect(),
+            Self::CenterAll => windows.iter_mut().filter(|w| !w.is_centered()).collect::<Vec<_>>(),
+        };
+
+        let moves = targets
+            .into_iter()
+            .filter_map(|w| {
+                WindowMove::perform(w, false, WindowInfo::center, WindowInfo::centered_rect)
+                    .inspect_err(|e| log::error!("Failed to center \"{}\": {}", w.title(), e))
+                    .ok()
+            })
+            .collect();
+
+        Ok(moves)
+    }
+
+    /// Whether the action can be done on this platform. Windows are only listed and moved on
+    /// Windows, so elsewhere only the overlay can be shown.
+    pub fn is_supported(self) -> bool {
+        cfg!(target_os = "windows") || self == Self::ShowOverlay
+    }
+}
+
+impl fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::ShowOverlay => "show the overlay",
+            Self::CenterActive => "center the active window",
+            Self::CenterUnderCursor => "center the window under the cursor",
+            Self::CenterAll => "center every window",
+        })
+    }
+}
+
+
+/**
+Represents the hotkeys block of the preferences: the hotkey of every action, if it has one. They're
+only listened to while the program runs in the background (see the daemon command).
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HotkeyPreferences {
+    pub show_overlay: Option<Hotkey>,
+    pub center_active: Option<Hotkey>,
+    pub center_under_cursor: Option<Hotkey>,
+    pub center_all: Option<Hotkey>,
+}
+
+impl Default for HotkeyPreferences {
+    fn default() -> Self {
+        Self {
+            show_overlay: "Ctrl+Alt+C".parse().ok(),
+            center_active: "Ctrl+Alt+X".parse().ok(),
+            center_under_cursor: None,
+            center_all: None,
+        }
+    }
+}
+
+impl HotkeyPreferences {
+    /// Returns every hotkey that was set, alongside its action.
+    pub fn bindings(&self) -> Vec<(Hotkey, HotkeyAction)> {
+        [
+            (self.show_overlay, HotkeyAction::ShowOverlay),
+            (self.center_active, HotkeyAction::CenterActive),
+            (self.center_under_cursor, HotkeyAction::CenterUnderCursor),
+            (self.center_all, HotkeyAction::CenterAll),
+        ]
+        .into_iter()
+        .filter_map(|(hotkey, action)| hotkey.map(|h| (h, action)))
+        .collect()
+    }
+}
+
+
+/// Registers the given hotkeys with the system, and sends their action to the event loop every
+/// time one of them is pressed. Hotkeys are listened to on their own thread, for as long as the
+/// event loop runs.
+///
+/// Hotkeys that can't be registered, usually because another program already uses them or their
+/// action isn't supported on this platform, are skipped and returned with the reason they failed.
+/// An error is returned if none can be listened to at all.
+pub fn listen_hotkeys(
+    bindings: Vec<(Hotkey, HotkeyAction)>,
+    proxy: EventLoopProxy<OverlayEvent>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if bindings.is_empty() {
+        return Err("no hotkeys are set in the preferences".into());
+    }
+
+    let count = bindings.len();
+
+    let (bindings, unsupported): (Vec<_>, Vec<_>) = bindings
+        .into_iter()
+        .partition(|(_, action)| action.is_supported());
+
+    let mut failures = unsupported
+        .into_iter()
+        .map(|(hotkey, action)| format!("{hotkey} ({action}): windows can only be moved on Windows"))
+        .collect::<Vec<_>>();
+
+    if bindings.is_empty() {
+        return Err(format!("no hotkey could be registered: {}", failures.join("; ")).into());
+    }
+
+    let (ready, registered) = mpsc::channel();
+
+    thread::Builder::new()
+        .name(String::from("hotkeys"))
+        .spawn(move || run(bindings, proxy, ready))?;
+
+    failures.extend(registered.recv()??);
+
+    if failures.len() == count {
+        return Err(format!("no hotkey could be registered: {}", failures.join("; ")).into());
+    }
+
+    Ok(failures)
+}
+
+/// Registers the hotkeys, reports whether they can be listened to and then waits for them.
+#[cfg(target_os = "windows")]
+fn run(
+    bindings: Vec<(Hotkey, HotkeyAction)>,
+    proxy: EventLoopProxy<OverlayEvent>,
+    ready: mpsc::Sender<Result<Vec<String>, String>>,
+) {
+    let mut failures = Vec::new();
+
+    // Hotkeys registered without a window are posted to the message queue of this thread.
+    for (id, (hotkey, action)) in bindings.iter().enumerate() {
+        match unsafe { RegisterHotKey(None, id as i32, hotkey.modifiers(), hotkey.virtual_key().0 as u32) } {
+            Ok(()) => log::info!("Registered {} to {}.", hotkey, action),
+            Err(e) => {
+                log::warn!("Could not register {} to {}: {}", hotkey, action, e);
+                failures.push(format!("{hotkey} ({action}): {e}"));
+            }
+        }
+    }
+
+    let nothing_registered = failures.len() == bindings.len();
+    let _ = ready.send(Ok(failures));
+
+    if nothing_registered {
+        return;
+    }
+
+    let mut message = MSG::default();
+
+    while unsafe { GetMessageW(&mut message, None, 0, 0) }.as_bool() {
+        if message.message != WM_HOTKEY {
+            continue;
+        }
+
+        if let Some((_, action)) = bindings.get(message.wParam.0) {
+            // The event loop is gone, so there's no one left to listen to.
+            if proxy.send_event(OverlayEvent::Hotkey(*action)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Grabs the hotkeys on the root window of the X server, reports whether they can be listened to
+/// and then waits for them.
+#[cfg(target_os = "linux")]
+fn run(
+    bindings: Vec<(Hotkey, HotkeyAction)>,
+    proxy: EventLoopProxy<OverlayEvent>,
+    ready: mpsc::Sender<Result<Vec<String>, String>>,
+) {
+    let (connection, screen) = match x11rb::connect(None) {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = ready.send(Err(format!("Could not connect to the X server: {e}")));
+            return;
+        }
+    };
+
+    let root = connection.setup().roots[screen].root;
+    let min_keycode = connection.setup().min_keycode;
+    let max_keycode = connection.setup().max_keycode;
+
+    let mapping = match connection
+        .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+        .map_err(|e| e.to_string())
+        .and_then(|c| c.reply().map_err(|e| e.to_string()))
+    {
+        Ok(m) => m,
+        Err(e) => {
+            let _ = ready.send(Err(format!("Could not read the keyboard mapping: {e}")));
+            return;
+        }
+    };
+
+    // Caps Lock and Num Lock are modifiers as well, so every hotkey is grabbed with and without
+    // them to work regardless of their state.
+    let locks = [ModMask::from(0u16), ModMask::LOCK, ModMask::M2, ModMask::LOCK | ModMask::M2];
+
+    let keycode_of = |keysym: u32| {
+        mapping.keysyms
+            .chunks(mapping.keysyms_per_keycode.max(1) as usize)
+            .position(|syms| syms.contains(&keysym))
+            .map(|index| min_keycode + index as u8)
+    };
+
+    let mut grabbed = Vec::new();
+    let mut failures = Vec::new();
+
+    for (hotkey, action) in &bindings {
+        let Some(keycode) = keycode_of(hotkey.keysym()) else {
+            log::warn!("Could not register {} to {}: the key isn't on the keyboard", hotkey, action);
+            failures.push(format!("{hotkey} ({action}): the key isn't on the keyboard"));
+            continue;
+        };
+
+        let mut locked = Vec::new();
+
+        let result = locks.iter().try_for_each(|lock| {
+            let modifiers = hotkey.modifiers() | *lock;
+
+            connection
+                .grab_key(false, root, modifiers, keycode, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(|e| e.to_string())?
+                .check()
+                .map_err(|e| e.to_string())?;
+
+            locked.push(modifiers);
+
+            Ok::<_, String>(())
+        });
+
+        // A hotkey that's only grabbed with some of the locks would swallow its key presses
+        // without ever being handled, so what was grabbed of it is let go.
+        if result.is_err() {
+            for modifiers in locked {
+                let ungrabbed = connection
+                    .ungrab_key(keycode, root, modifiers)
+                    .map_err(|e| e.to_string())
+                    .and_then(|c| c.check().map_err(|e| e.to_string()));
+
+                if let Err(e) = ungrabbed {
+                    log::warn!("Could not let go of {}: {}", hotkey, e);
+                }
+            }
+        }
+
+        match result {
+            Ok(()) => {
+                log::info!("Registered {} to {}.", hotkey, action);
+                grabbed.push((keycode, u16::from(hotkey.modifiers()), *action));
+            }
+            Err(e) => {
+                log::warn!("Could not register {} to {}: {}", hotkey, action, e);
+                failures.push(format!("{hotkey} ({action}): {e}"));
+            }
+        }
+    }
+
+    let _ = ready.send(Ok(failures));
+
+    if grabbed.is_empty() {
+        return;
+    }
+
+    let ignored = u16::from(ModMask::LOCK | ModMask::M2);
+
+    loop {
+        let event = match connection.wait_for_event() {
+            Ok(e) => e,
+            Err(e) => {
+                log::error!("Lost the connection to the X server, hotkeys stopped working: {}", e);
+                break;
+            }
+        };
+
+        let Event::KeyPress(press) = event else {
+            continue;
+        };
+
+        let modifiers = u16::from(press.state) & !ignored;
+
+        let action = grabbed
+            .iter()
+            .find(|(keycode, m, _)| *keycode == press.detail && *m == modifiers)
+            .map(|(_, _, action)| *action);
+
+        if let Some(action) = action {
+            // The event loop is gone, so there's no one left to listen to.
+            if proxy.send_event(OverlayEvent::Hotkey(action)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn run(
+    _bindings: Vec<(Hotkey, HotkeyAction)>,
+    _proxy: EventLoopProxy<OverlayEvent>,
+    ready: mpsc::Sender<Result<Vec<String>, String>>,
+) {
+    let _ = ready.send(Err(String::from("Global hotkeys aren't supported on this platform")));
+}
@@ -1,33 +1,62 @@
+mod display;
+mod hotkey;
 mod overlay;
+mod preview;
+mod software;
+mod text;
+mod theme;
+pub use display::*;
+pub use hotkey::*;
 pub use overlay::*;
+pub use preview::*;
+pub use text::*;
+pub use theme::*;
 
 use std::{fmt, fs};
+use std::sync::Mutex;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 
+#[cfg(target_os = "windows")]
+use windows::core::PWSTR;
+
 #[cfg(target_os = "windows")]
 use windows::Win32::{
-    Foundation::{BOOL, HWND, LPARAM, RECT, TRUE},
+    Foundation::{BOOL, CloseHandle, HWND, LPARAM, POINT, RECT, TRUE},
     Graphics::{
         Dwm::{
             DwmGetWindowAttribute,
             DWMWA_EXTENDED_FRAME_BOUNDS,
         },
         Gdi::{
+            EnumDisplayMonitors,
             GetMonitorInfoW,
+            HDC,
             HMONITOR,
             MONITOR_DEFAULTTONEAREST,
             MonitorFromWindow,
-            MONITORINFO
+            MONITORINFO,
+            MONITORINFOEXW,
         }
     },
+    System::Threading::{
+        GetCurrentProcessId,
+        OpenProcess,
+        PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+        QueryFullProcessImageNameW,
+    },
     UI::WindowsAndMessaging::{
 
         EnumWindows,
+        GetClassNameW,
+        GetCursorPos,
+        GetForegroundWindow,
         GetWindowTextW,
+        GetWindowThreadProcessId,
         GWL_EXSTYLE,
         GWL_STYLE,
         GetWindowLongPtrW,
@@ -37,6 +66,9 @@ use windows::Win32::{
         IsZoomed,
         SET_WINDOW_POS_FLAGS,
         SetWindowPos,
+        SPI_GETCLIENTAREAANIMATION,
+        SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+        SystemParametersInfoW,
         SWP_NOACTIVATE,
         SWP_NOSIZE,
         SWP_NOZORDER,
@@ -51,9 +83,16 @@ use windows::Win32::{
 /// The location of the preferences file, relative to the program's directory.
 const PREFERENCES_FILE: &str = "config.yml";
 
+/// The location of the list of applications hidden from the overlay, relative to the program's
+/// directory. Unlike the preferences, this file is written by the program.
+const IGNORED_APPS_FILE: &str = "ignored_apps.yml";
+
 /// The global user-defined preferences. Accessible through the [`Preferences::get`] method.
 static mut PREFERENCES: Option<Preferences> = None;
 
+/// The applications hidden from the overlay. Loaded from the [`IGNORED_APPS_FILE`] on first use.
+static IGNORED_APPS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
 
 /// Initializes the logger.
 pub fn init_logger() -> Result<(), flexi_logger::FlexiLoggerError> {
@@ -73,7 +112,7 @@ pub fn init_logger() -> Result<(), flexi_logger::FlexiLoggerError> {
                 "[{} {}]: {}",
                 now.now().format("%Y-%m-%d %H:%M:%S"),      // Timestamp.
                 record.level(),                             // Log Level.
-                &record.args(),                             // Message.
+                record.args(),                              // Message.
             ))
         })
         .rotate(
@@ -111,7 +150,7 @@ impl Error for GenericError {}
 /// Represents the bounding rectangle of a quad.
 ///
 /// Not to be confused with the Windows API RECT struct.
-#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize, Serialize)]
 #[repr(C)]
 pub struct Rect {
     pub left: i32,
@@ -147,11 +186,70 @@ impl Rect {
         }
     }
     
+    /// Constructs the smallest rect that contains both of the given corner points, regardless of
+    /// the order in which they are provided.
+    pub fn from_points(a: PhysicalPosition<i32>, b: PhysicalPosition<i32>) -> Rect {
+        Rect {
+            left: a.x.min(b.x),
+            top: a.y.min(b.y),
+            right: a.x.max(b.x),
+            bottom: a.y.max(b.y),
+        }
+    }
+
+    /// Returns the smallest rect that contains both of the given rects.
+    pub fn union(a: Rect, b: Rect) -> Rect {
+        Rect {
+            left: a.left.min(b.left),
+            top: a.top.min(b.top),
+            right: a.right.max(b.right),
+            bottom: a.bottom.max(b.bottom),
+        }
+    }
+    
     /// Checks whether the given coordinate is contained by the bounding rect.
     pub fn contains(&self, x: i32, y: i32) -> bool {
         x >= self.left && x < self.right && y >= self.top && y < self.bottom
     }
+
+    /// Checks whether both rects share at least one coordinate.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.left < other.right && other.left < self.right &&
+            self.top < other.bottom && other.top < self.bottom
+    }
+
+    /// Returns the area shared by both rects, if they intersect.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        self.intersects(other).then(|| Rect {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        })
+    }
+
+    /// Moves the rect the least it takes to be inside of the given area, keeping its size. Rects
+    /// larger than the area keep their top-left corner inside of it.
+    pub fn clamp_into(&self, area: &Rect) -> Rect {
+        let left = self.left.min(area.right - self.width() as i32).max(area.left);
+        let top = self.top.min(area.bottom - self.height() as i32).max(area.top);
+
+        Rect::new(left, top, self.width(), self.height())
+    }
+
+    pub fn width(&self) -> u32 {
+        (self.right - self.left) as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        (self.bottom - self.top) as u32
+    }
     
+    /// Returns the amount of pixels covered by the rect.
+    pub fn area(&self) -> u64 {
+        self.width() as u64 * self.height() as u64
+    }
+
     /// Returns a tuple containing the four attributes of a rect: (left, top, right, bottom) to allow
     /// for data manipulation of the bounding rect.
     pub fn raw(&self) -> (i32, i32, i32, i32) {
@@ -164,12 +262,19 @@ impl Rect {
 #[derive(Debug)]
 pub struct WindowInfo {
     title: String,
+    /// The name of the application that owns the window, if it could be determined.
+    app: Option<String>,
+    /// The name of the class the window was registered with by its application.
+    class: String,
     
     position: PhysicalPosition<i32>,
     size: PhysicalSize<u32>,
 
     monitor: MonitorInfo,
 
+    /// Uniquely identifies the window while it exists. On Windows, this is the window's handle.
+    id: isize,
+
     #[cfg(target_os = "windows")]
     handle: HWND,
 }
@@ -178,8 +283,8 @@ impl fmt::Display for WindowInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{:?} ({}, {}) [{}x{}] {}",
-            self.handle,
+            "{:#x} ({}, {}) [{}x{}] {}",
+            self.id,
             self.position.x,
             self.position.y,
             self.size.width,
@@ -199,7 +304,17 @@ impl WindowInfo {
             return Err(GenericError::InvalidData.into());
         }
 
-        if IsWindow(hwnd).as_bool() == false {
+        if !IsWindow(hwnd).as_bool() {
+            return Err(GenericError::InvalidData.into());
+        }
+
+        let mut process_id = 0;
+
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+
+        // The overlay's own windows are never listed, since windows are enumerated again while
+        // they're open.
+        if process_id == GetCurrentProcessId() {
             return Err(GenericError::InvalidData.into());
         }
 
@@ -223,17 +338,17 @@ impl WindowInfo {
         let style = GetWindowLongPtrW(hwnd, GWL_STYLE) as u32;
 
         // Check if the window has a parent window.
-        if (style & WS_CHILD.0) != 0 && preferences.allow_child_ws == false {
+        if (style & WS_CHILD.0) != 0 && !preferences.allow_child_ws {
             return Err(GenericError::InvalidData.into());
         }
 
         // Check if the window can be considered a popup.
-        if (style & WS_POPUP.0) != 0 && preferences.allow_popup_ws == false {
+        if (style & WS_POPUP.0) != 0 && !preferences.allow_popup_ws {
             return Err(GenericError::InvalidData.into());
         }
 
         // A window should be visible, otherwise it could be an overlay or hidden process.
-        if IsWindowVisible(hwnd).as_bool() == false {
+        if !IsWindowVisible(hwnd).as_bool() {
             return Err(GenericError::InvalidData.into());
         }
 
@@ -248,18 +363,60 @@ impl WindowInfo {
             size_of::<RECT>() as u32,
         )?;
 
+        let app = Self::find_app_name(hwnd);
+
+        let mut class_buffer = [0u16; 256];
+        let class_length = GetClassNameW(hwnd, &mut class_buffer);
+
+        // The user chose to never see windows of this application in the overlay.
+        if app.as_deref().is_some_and(is_app_ignored) {
+            return Err(GenericError::InvalidData.into());
+        }
+
         let monitor_handle = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
         let monitor_info = MonitorInfo::build(monitor_handle)?;
 
         Ok(Self {
+            id: hwnd.0 as isize,
             handle: hwnd,
             monitor: monitor_info,
             title: String::from_utf16_lossy(&buffer[..length as usize]),
+            app,
+            class: String::from_utf16_lossy(&class_buffer[..class_length.max(0) as usize]),
             position: PhysicalPosition::new(rect.left, rect.top),
             size: PhysicalSize::new((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32),
         })
     }
     
+    /// Finds the name of the executable that owns the window, without its extension.
+    unsafe fn find_app_name(hwnd: HWND) -> Option<String> {
+        let mut process_id = 0;
+
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut length,
+        );
+
+        let _ = CloseHandle(process);
+
+        result.ok()?;
+
+        let path = String::from_utf16_lossy(&buffer[..length as usize]);
+
+        std::path::Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+    }
+    
     unsafe fn is_maximized(&self) -> bool {
         IsZoomed(self.handle).as_bool()
     }
@@ -270,37 +427,226 @@ impl WindowInfo {
 }
 
 impl WindowInfo {
+    /// Returns a value that uniquely identifies the window while it exists.
+    pub fn id(&self) -> isize {
+        self.id
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn app(&self) -> Option<&str> {
+        self.app.as_deref()
+    }
+
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        self.position
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    /// Returns the monitor the window is centered on.
+    pub fn monitor(&self) -> &MonitorInfo {
+        &self.monitor
+    }
+
+    /// Scores how well the given query matches the window's title or application name.
+    /// Returns `None` if neither of them matches.
+    pub fn search_score(&self, query: &str) -> Option<i32> {
+        let title_score = fuzzy_score(query, &self.title);
+        let app_score = self.app
+            .as_ref()
+            .and_then(|app| fuzzy_score(query, app));
+
+        title_score.max(app_score)
+    }
+
+    /// Calculates how much of the window (from 0 to 1) can be seen on the given monitors.
+    pub fn visible_fraction(&self, monitors: &[MonitorInfo]) -> f64 {
+        let rect = self.rect();
+
+        if rect.area() == 0 {
+            return 0.0;
+        }
+
+        // Monitors never overlap, so the visible parts of the window can simply be added up.
+        let visible = monitors
+            .iter()
+            .filter_map(|m| rect.intersection(&m.bounds))
+            .map(|r| r.area())
+            .sum::<u64>();
+
+        visible as f64 / rect.area() as f64
+    }
+
     /// Check if the window is centered.
     pub fn is_centered(&self) -> bool {
-        let monitor_position = self.monitor.position;
-        let monitor_size = self.monitor.size;
-        let x = monitor_position.x + ((monitor_size.width / 2) as i32) - ((self.size.width / 2) as i32);
-        let y = monitor_position.y + ((monitor_size.height / 2) as i32) - ((self.size.height / 2) as i32);
-        
-        self.position.x == x && self.position.y == y
+        self.center_offset() == (0, 0)
+    }
+
+    /// Calculates how far the window is from being centered, as an (x, y) offset in pixels.
+    /// Positive values mean the window is to the right of or below the center.
+    pub fn center_offset(&self) -> (i32, i32) {
+        let centered = self.centered_position();
+
+        (self.position.x - centered.x, self.position.y - centered.y)
+    }
+
+    /// Calculates the position the window would have if it were centered on it's corresponding
+    /// monitor.
+    pub fn centered_position(&self) -> PhysicalPosition<i32> {
+        self.monitor.center_of(self.size)
+    }
+    
+    /// Calculates the rect the window would occupy if it were centered. This is the same area
+    /// [`WindowInfo::center`] moves the window to.
+    pub fn centered_rect(&self) -> Rect {
+        self.centered_rect_on(&self.monitor)
+    }
+
+    /// Calculates the rect the window would occupy if it were centered on the given monitor.
+    pub fn centered_rect_on(&self, monitor: &MonitorInfo) -> Rect {
+        let position = monitor.center_of(self.size);
+
+        Rect::new(position.x, position.y, self.size.width, self.size.height)
     }
     
     /// Tries to position the window to the center of it's corresponding monitor.
-    pub fn center(&self) -> Result<(), Box<dyn Error>> {
-        let monitor_position = self.monitor.position;
-        let monitor_size = self.monitor.size;
-        let x = monitor_position.x + ((monitor_size.width / 2) as i32) - ((self.size.width / 2) as i32);
-        let y = monitor_position.y + ((monitor_size.height / 2) as i32) - ((self.size.height / 2) as i32);
+    pub fn center(&mut self) -> Result<(), Box<dyn Error>> {
+        let position = self.centered_position();
+
+        self.move_to(position)?;
         
+        log::debug!("Repositioned window to the center: {self}");
+
+        Ok(())
+    }
+
+    /// Tries to center the window horizontally, keeping its vertical position.
+    pub fn center_horizontally(&mut self) -> Result<(), Box<dyn Error>> {
+        let position = PhysicalPosition::new(self.centered_position().x, self.position.y);
+
+        self.move_to(position)?;
+
+        log::debug!("Repositioned window to the horizontal center: {self}");
+
+        Ok(())
+    }
+
+    /// Tries to center the window vertically, keeping its horizontal position.
+    pub fn center_vertically(&mut self) -> Result<(), Box<dyn Error>> {
+        let position = PhysicalPosition::new(self.position.x, self.centered_position().y);
+
+        self.move_to(position)?;
+
+        log::debug!("Repositioned window to the vertical center: {self}");
+
+        Ok(())
+    }
+
+    /// Calculates the rect the window would occupy if it were shrunk to fit inside of its monitor
+    /// and centered. This is the same area [`WindowInfo::fit_and_center`] moves the window to.
+    pub fn fitted_rect(&self) -> Rect {
+        let size = PhysicalSize::new(
+            self.size.width.min(self.monitor.size.width),
+            self.size.height.min(self.monitor.size.height),
+        );
+        let position = self.monitor.center_of(size);
+
+        Rect::new(position.x, position.y, size.width, size.height)
+    }
+
+    /// Tries to shrink the window so it fits inside of its monitor, then centers it.
+    pub fn fit_and_center(&mut self) -> Result<(), Box<dyn Error>> {
+        self.set_rect(self.fitted_rect())?;
+
+        log::debug!("Resized and repositioned window to the center: {self}");
+
+        Ok(())
+    }
+
+    /// Tries to center the window on the given monitor, which becomes its corresponding monitor.
+    pub fn center_on(&mut self, monitor: MonitorInfo) -> Result<(), Box<dyn Error>> {
+        self.monitor = monitor;
+
+        self.center()
+    }
+
+    /// Calculates the rect the window would occupy if it were dropped on the given monitor:
+    /// centered on it, but kept out of the taskbar and other docked toolbars.
+    pub fn dropped_rect_on(&self, monitor: &MonitorInfo) -> Rect {
+        self.centered_rect_on(monitor).clamp_into(&monitor.work_area)
+    }
+
+    /// Tries to move the window to where it would be dropped on the given monitor (see
+    /// [`WindowInfo::dropped_rect_on`]), which becomes its corresponding monitor.
+    pub fn drop_on(&mut self, monitor: MonitorInfo) -> Result<(), Box<dyn Error>> {
+        let rect = self.dropped_rect_on(&monitor);
+
+        self.monitor = monitor;
+        self.move_to(PhysicalPosition::new(rect.left, rect.top))?;
+
+        log::debug!("Dropped window on another monitor: {self}");
+
+        Ok(())
+    }
+
+    /// Tries to position the top-left corner of the window at the given coordinates, keeping its
+    /// current size.
+    pub fn move_to(&mut self, position: PhysicalPosition<i32>) -> Result<(), Box<dyn Error>> {
         #[cfg(target_os = "windows")]
         unsafe {
             SetWindowPos(
                 self.handle,
                 None,
-                x,
-                y,
+                position.x,
+                position.y,
                 0, // This gets ignored because of the no size flag.
                 0, // This gets ignored because of the no size flag.
                 SET_WINDOW_POS_FLAGS(SWP_NOSIZE.0 | SWP_NOZORDER.0 | SWP_NOACTIVATE.0),
             )?;
         }
-        
-        log::debug!("Repositioned window to the center: {self}");
+
+        self.position = position;
+
+        Ok(())
+    }
+
+    /// Tries to move the window by the given amount of pixels.
+    pub fn move_by(&mut self, x: i32, y: i32) -> Result<(), Box<dyn Error>> {
+        let position = PhysicalPosition::new(self.position.x + x, self.position.y + y);
+
+        self.move_to(position)?;
+
+        log::debug!("Nudged window by ({x}, {y}): {self}");
+
+        Ok(())
+    }
+
+    /// Tries to move and resize the window so that it occupies the given rect.
+    pub fn set_rect(&mut self, rect: Rect) -> Result<(), Box<dyn Error>> {
+        #[cfg(target_os = "windows")]
+        unsafe {
+            SetWindowPos(
+                self.handle,
+                None,
+                rect.left,
+                rect.top,
+                rect.width() as i32,
+                rect.height() as i32,
+                SET_WINDOW_POS_FLAGS(SWP_NOZORDER.0 | SWP_NOACTIVATE.0),
+            )?;
+        }
+
+        self.position = PhysicalPosition::new(rect.left, rect.top);
+        self.size = PhysicalSize::new(rect.width(), rect.height());
 
         Ok(())
     }
@@ -316,29 +662,204 @@ impl WindowInfo {
 }
 
 
+/// Returns how far every window of a group has to move for their combined bounding rect to be
+/// centered on the monitor of the first one, `None` if there are no windows.
+pub fn group_offset(windows: &[&mut WindowInfo]) -> Option<PhysicalPosition<i32>> {
+    let first = windows.first()?;
+
+    let bounds = windows
+        .iter()
+        .fold(first.rect(), |bounds, w| Rect::union(bounds, w.rect()));
+
+    let target = first.monitor.center_of(PhysicalSize::new(bounds.width(), bounds.height()));
+
+    Some(PhysicalPosition::new(target.x - bounds.left, target.y - bounds.top))
+}
+
+
+/// A window moved (or that would be moved) by a command, such as [`rescue_windows`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowMove {
+    pub id: isize,
+    pub title: String,
+    /// Where the window was before being moved.
+    pub from: Rect,
+    pub to: Rect,
+}
+
+impl WindowMove {
+    /// Moves the window to the rect given by the function, or only reports where it would go if
+    /// it's a dry run.
+    pub fn perform(
+        window: &mut WindowInfo,
+        dry_run: bool,
+        move_fn: impl FnOnce(&mut WindowInfo) -> Result<(), Box<dyn Error>>,
+        target: impl FnOnce(&WindowInfo) -> Rect,
+    ) -> Result<Self, Box<dyn Error>> {
+        let from = window.rect();
+
+        let to = match dry_run {
+            true => target(window),
+            false => {
+                move_fn(window)?;
+                window.rect()
+            }
+        };
+
+        Ok(Self { id: window.id, title: window.title.clone(), from, to })
+    }
+}
+
+impl fmt::Display for WindowMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: ({}, {}) [{}x{}] -> ({}, {}) [{}x{}]",
+            self.title,
+            self.from.left,
+            self.from.top,
+            self.from.width(),
+            self.from.height(),
+            self.to.left,
+            self.to.top,
+            self.to.width(),
+            self.to.height()
+        )
+    }
+}
+
+/// A window a command failed to move, such as [`rescue_windows`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MoveFailure {
+    pub id: isize,
+    pub title: String,
+    pub error: String,
+}
+
+impl MoveFailure {
+    pub fn new(window: &WindowInfo, error: &dyn Error) -> Self {
+        Self { id: window.id, title: window.title.clone(), error: error.to_string() }
+    }
+}
+
+impl fmt::Display for MoveFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title, self.error)
+    }
+}
+
+/// Brings back the windows that can barely be seen on any of the given monitors, usually left
+/// behind by a monitor that was disconnected. A window is rescued when less of it than the given
+/// threshold (usually [`Preferences::rescue_threshold`]) is visible, by fitting and centering it on
+/// its monitor (the one nearest to it). Nothing is moved on a dry run.
+///
+/// Returns every window that was moved or failed to move, as a report of what happened.
+pub fn rescue_windows(
+    windows: &mut [WindowInfo],
+    monitors: &[MonitorInfo],
+    threshold: f64,
+    dry_run: bool,
+) -> Vec<Result<WindowMove, MoveFailure>> {
+    let mut rescues = Vec::new();
+
+    for window in windows.iter_mut() {
+        if window.visible_fraction(monitors) >= threshold {
+            continue;
+        }
+
+        match WindowMove::perform(window, dry_run, WindowInfo::fit_and_center, WindowInfo::fitted_rect) {
+            Ok(rescue) => {
+                log::info!("Rescued off-screen window {rescue}");
+                rescues.push(Ok(rescue));
+            }
+            Err(e) => {
+                log::error!("Could not rescue the window \"{}\": {}", window.title, e);
+                rescues.push(Err(MoveFailure::new(window, e.as_ref())));
+            }
+        }
+    }
+
+    rescues
+}
+
+
 /// Represents information for a display screen (monitor) detected in the system.
-#[derive(Debug, Copy, Clone)]
+///
+/// The position and size describe the area windows are centered on, which depends on the
+/// [`Preferences::use_absolute_area`] preference.
+#[derive(Debug, Clone)]
 pub struct MonitorInfo {
+    name: String,
+
     position: PhysicalPosition<i32>,
     size: PhysicalSize<u32>,
+
+    /// The full area of the monitor.
+    bounds: Rect,
+    /// The area of the monitor that isn't covered by the taskbar or other docked toolbars.
+    work_area: Rect,
+}
+
+impl MonitorInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn work_area(&self) -> Rect {
+        self.work_area
+    }
+
+    /// Returns the area windows are centered on.
+    pub fn rect(&self) -> Rect {
+        Rect::new(
+            self.position.x,
+            self.position.y,
+            self.size.width,
+            self.size.height,
+        )
+    }
+
+    /// Calculates the top-left position an area of the given size must have to be centered on
+    /// the monitor.
+    pub fn center_of(&self, size: PhysicalSize<u32>) -> PhysicalPosition<i32> {
+        PhysicalPosition::new(
+            self.position.x + ((self.size.width / 2) as i32) - ((size.width / 2) as i32),
+            self.position.y + ((self.size.height / 2) as i32) - ((size.height / 2) as i32),
+        )
+    }
 }
 
 #[cfg(target_os = "windows")]
 impl MonitorInfo {
+    /// Constructs the information of the monitor with the given handle.
+    ///
+    /// # Safety
+    ///
+    /// The handle has to be a monitor handle provided by the system, such as the ones given while
+    /// enumerating the monitors.
     pub unsafe fn build(handle: HMONITOR) -> Result<Self, Box<dyn Error>> {
         if handle.is_invalid() {
             return Err(GenericError::InvalidData.into());
         }
 
-        let mut monitor_info = MONITORINFO {
-            cbSize: size_of::<MONITORINFO>() as u32,
+        // The extended structure also contains the name of the monitor.
+        let mut monitor_info_ex = MONITORINFOEXW {
+            monitorInfo: MONITORINFO {
+                cbSize: size_of::<MONITORINFOEXW>() as u32,
+                ..Default::default()
+            },
             ..Default::default()
         };
         
-        if GetMonitorInfoW(handle, &mut monitor_info).as_bool() == false {
+        if !GetMonitorInfoW(handle, &mut monitor_info_ex as *mut _ as *mut MONITORINFO).as_bool() {
             return Err(GenericError::InvalidData.into());
         }
 
+        let monitor_info = monitor_info_ex.monitorInfo;
         let preferences = Preferences::get();
 
         let rect = if preferences.use_absolute_area {
@@ -347,12 +868,21 @@ impl MonitorInfo {
             monitor_info.rcWork
         };
 
+        let to_rect = |r: RECT| Rect { left: r.left, top: r.top, right: r.right, bottom: r.bottom };
+        let name_length = monitor_info_ex.szDevice
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(monitor_info_ex.szDevice.len());
+
         Ok(Self {
+            name: String::from_utf16_lossy(&monitor_info_ex.szDevice[..name_length]),
             position: PhysicalPosition::new(rect.left, rect.top),
             size: PhysicalSize::new(
                 (rect.right - rect.left) as u32,
                 (rect.bottom - rect.top) as u32
             ),
+            bounds: to_rect(monitor_info.rcMonitor),
+            work_area: to_rect(monitor_info.rcWork),
         })
     }
 }
@@ -360,21 +890,44 @@ impl MonitorInfo {
 
 /// Returns a list of the currently active and visible application windows in the operating system.
 pub fn get_windows() -> Result<Vec<WindowInfo>, Box<dyn Error>> {
-    let mut windows: Vec<WindowInfo> = Vec::new();
+    let windows = enumerate_windows(false)?;
+
+    if windows.is_empty() {
+        log::info!("No windows found.");
+    }
+
+    Ok(windows)
+}
+
+/// Returns a list of the active and visible application windows that are already centered, which
+/// [`get_windows`] leaves out.
+pub fn get_centered_windows() -> Result<Vec<WindowInfo>, Box<dyn Error>> {
+    enumerate_windows(true)
+}
+
+/// The windows collected while enumerating, and whether the centered ones are collected instead
+/// of the rest.
+struct WindowEnumeration {
+    // Only read while enumerating, which is done on Windows.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    centered: bool,
+    windows: Vec<WindowInfo>,
+}
+
+fn enumerate_windows(centered: bool) -> Result<Vec<WindowInfo>, Box<dyn Error>> {
+    // The list is only filled in on Windows.
+    #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
+    let mut enumeration = WindowEnumeration { centered, windows: Vec::new() };
 
     #[cfg(target_os = "windows")]
     unsafe {
         EnumWindows(
             Some(window_enum_proc),
-            LPARAM(&mut windows as *mut _ as isize) // Casting to a mutable pointer.
+            LPARAM(&mut enumeration as *mut _ as isize) // Casting to a mutable pointer.
         )?;
     }
 
-    if windows.is_empty() {
-        log::info!("No windows found.");
-    }
-
-    Ok(windows)
+    Ok(enumeration.windows)
 }
 
 #[cfg(target_os = "windows")]
@@ -387,30 +940,297 @@ unsafe extern "system" fn window_enum_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
             return TRUE;
         }
     };
-    
+
+    // Casting of LPARAM pointer to the enumeration.
+    let enumeration = &mut *(lparam.0 as *mut WindowEnumeration);
+
     // There's no point in repositioning these windows.
-    if window.is_maximized() || window.is_minimized() || window.is_centered() {
+    if window.is_maximized() || window.is_minimized() || window.is_centered() != enumeration.centered {
         return TRUE;
     }
 
     log::debug!("Collected window: {window}");
 
-    // Casting of LPARAM pointer to a Vec.
-    let window_list = &mut *(lparam.0 as *mut Vec<WindowInfo>);
+    enumeration.windows.push(window);
+
+    TRUE
+}
 
-    window_list.push(window);
+
+/// Returns a list of the monitors (displays) connected to the system.
+pub fn get_monitors() -> Result<Vec<MonitorInfo>, Box<dyn Error>> {
+    // The list is only filled in on Windows.
+    #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(monitor_enum_proc),
+            LPARAM(&mut monitors as *mut _ as isize) // Casting to a mutable pointer.
+        ).ok()?;
+    }
+
+    Ok(monitors)
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn monitor_enum_proc(
+    handle: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    lparam: LPARAM
+) -> BOOL {
+    // Invalid monitors are skipped and the enumeration continues.
+    if let Ok(monitor) = MonitorInfo::build(handle) {
+        // Casting of LPARAM pointer to a Vec.
+        let monitor_list = &mut *(lparam.0 as *mut Vec<MonitorInfo>);
+
+        monitor_list.push(monitor);
+    }
 
     TRUE
 }
 
 
+/// Checks whether the user chose to hide the windows of the given application from the overlay.
+pub fn is_app_ignored(app: &str) -> bool {
+    let mut ignored = IGNORED_APPS.lock().unwrap();
+
+    ignored
+        .get_or_insert_with(load_ignored_apps)
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case(app))
+}
+
+/// Hides the windows of the given application from the overlay, now and in future runs.
+pub fn ignore_app(app: &str) -> Result<(), Box<dyn Error>> {
+    let mut ignored = IGNORED_APPS.lock().unwrap();
+    let apps = ignored.get_or_insert_with(load_ignored_apps);
+
+    if !apps.iter().any(|a| a.eq_ignore_ascii_case(app)) {
+        apps.push(app.to_string());
+    }
+
+    let file = fs::File::create(IGNORED_APPS_FILE)?;
+    serde_yaml::to_writer(file, apps)?;
+
+    log::info!("Added \"{}\" to the ignored applications in \"{}\"", app, IGNORED_APPS_FILE);
+
+    Ok(())
+}
+
+/// Reads the list of ignored applications from the [`IGNORED_APPS_FILE`]. A missing file means
+/// that no application has been ignored yet.
+fn load_ignored_apps() -> Vec<String> {
+    let file = match fs::File::open(IGNORED_APPS_FILE) {
+        Ok(f) => f,
+        Err(_) => return Vec::new(),
+    };
+
+    serde_yaml::from_reader(file).unwrap_or_else(|e| {
+        log::error!("Failed to load ignored applications from \"{}\": {}", IGNORED_APPS_FILE, e);
+        Vec::new()
+    })
+}
+
+
+/// Scores how well the query fuzzy-matches the given text, ignoring case.
+///
+/// Every character of the query has to appear in the text in the same order, but not necessarily
+/// next to each other. Consecutive characters and characters at the start of a word are favored,
+/// while gaps between them are penalized. Returns `None` if the text doesn't match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = start + text[start..].iter().position(|c| *c == query_char)?;
+
+        score += 1;
+
+        if previous.is_some_and(|p| p + 1 == index) {
+            score += 5;
+        } else if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        // Characters skipped over cost a point each, up to a limit.
+        let gap = index - previous.map_or(0, |p| p + 1);
+        score -= gap.min(3) as i32;
+
+        previous = Some(index);
+        start = index + 1;
+    }
+
+    Some(score)
+}
+
+
+/// Checks whether animations should be skipped, either because the user turned them off in the
+/// preferences or because "Animation effects" are disabled in the system settings.
+pub fn prefers_reduced_motion() -> bool {
+    if Preferences::get().reduce_motion {
+        return true;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut enabled = TRUE;
+
+        let result = unsafe {
+            SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                Some(&mut enabled as *mut BOOL as *mut _),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+        };
+
+        match result {
+            Ok(_) => !enabled.as_bool(),
+            Err(e) => {
+                log::warn!("Could not query the system animation settings: {}", e);
+                false
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    false
+}
+
+
+/// Returns the id of the window in the foreground, the one the user is working with.
+pub fn get_active_window_id() -> Option<isize> {
+    #[cfg(target_os = "windows")]
+    {
+        let hwnd = unsafe { GetForegroundWindow() };
+
+        (!hwnd.is_invalid()).then_some(hwnd.0 as isize)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    None
+}
+
+/// Finds the window in the foreground among the given ones.
+pub fn find_active_window(windows: &mut [WindowInfo]) -> Option<&mut WindowInfo> {
+    let id = get_active_window_id()?;
+
+    windows.iter_mut().find(|w| w.id == id)
+}
+
+/// Finds the window under the cursor among the given ones, which are expected from the top-most
+/// down (as listed by [`get_windows`]), so the one found is the one the user sees.
+pub fn find_window_under_cursor(windows: &mut [WindowInfo]) -> Option<&mut WindowInfo> {
+    let cursor = get_cursor_position()?;
+
+    windows.iter_mut().find(|w| w.rect().contains(cursor.x, cursor.y))
+}
+
+/// Returns the position of the cursor on the desktop.
+pub fn get_cursor_position() -> Option<PhysicalPosition<i32>> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut point = POINT::default();
+
+        unsafe { GetCursorPos(&mut point) }.ok()?;
+
+        Some(PhysicalPosition::new(point.x, point.y))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    None
+}
+
+
+/// How the overlay is drawn.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RendererKind {
+    /// Uses the GPU, unless no adapter can be used.
+    #[default]
+    Auto,
+    Gpu,
+    /// Draws on the CPU, for machines where the GPU doesn't work well.
+    Software,
+}
+
+
+/// The graphics API the GPU renderer uses.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphicsBackend {
+    /// Lets wgpu pick the best API available.
+    #[default]
+    Auto,
+    Vulkan,
+    Gl,
+    Dx12,
+}
+
+
+/// Which adapter the GPU renderer prefers, on machines with more than one.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerPreference {
+    #[default]
+    Default,
+    /// Usually an integrated GPU.
+    LowPower,
+    /// Usually a dedicated GPU.
+    HighPerformance,
+}
+
+
+/// How frames drawn by the GPU renderer are synchronized with the display.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PresentMode {
+    /// Waits for the display, with the lowest latency the system supports.
+    #[default]
+    AutoVsync,
+    /// Doesn't wait for the display, if the system supports it.
+    AutoNoVsync,
+    Fifo,
+    Mailbox,
+    Immediate,
+}
+
+
 /// Represents the user preferences, contained by the [`PREFERENCES_FILE`].
+///
+/// Any preference missing from the file takes its default value.
 #[derive(Debug, Deserialize)]
+#[serde(default)]
 pub struct Preferences {
     pub allow_child_ws: bool,
     pub allow_popup_ws: bool,
+    pub backend: GraphicsBackend,
+    pub fade_duration: u64,
+    pub frame_latency: u32,
+    pub hotkeys: HotkeyPreferences,
+    pub hover_duration: u64,
     pub overlay_color: u32,
     pub overlay_opacity: f64,
+    pub power_preference: PowerPreference,
+    pub present_mode: PresentMode,
+    pub reduce_motion: bool,
+    pub renderer: RendererKind,
+    pub rescue_on_open: bool,
+    pub rescue_threshold: f64,
+    pub show_hints: bool,
+    pub show_guides: bool,
+    pub show_info: bool,
+    pub show_outlines: bool,
+    pub show_preview: bool,
+    pub theme: ThemePreferences,
     pub use_absolute_area: bool,
 }
 
@@ -419,8 +1239,25 @@ impl Default for Preferences {
         Self {
             allow_child_ws: true,
             allow_popup_ws: false,
+            backend: GraphicsBackend::Auto,
+            fade_duration: 150,
+            frame_latency: 2,
+            hotkeys: HotkeyPreferences::default(),
+            hover_duration: 120,
             overlay_color: 0,
             overlay_opacity: 0.6,
+            power_preference: PowerPreference::Default,
+            present_mode: PresentMode::AutoVsync,
+            reduce_motion: false,
+            renderer: RendererKind::Auto,
+            rescue_on_open: false,
+            rescue_threshold: 0.2,
+            show_hints: true,
+            show_guides: false,
+            show_info: false,
+            show_outlines: false,
+            show_preview: true,
+            theme: ThemePreferences::default(),
             use_absolute_area: false,
         }
     }
@@ -1,16 +1,370 @@
 #![windows_subsystem = "windows"]
 
+use std::error::Error;
+
 use centered_windows::*;
+use clap::{Args, Parser, Subcommand};
+use regex::Regex;
+use serde::Serialize;
+use winit::dpi::PhysicalPosition;
 use winit::event_loop::{ControlFlow, EventLoop};
 
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+
+/// Centers application windows on their monitors. Without a command, the overlay is opened.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Reports the windows that would be moved, without moving them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Prints the output as JSON instead of text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Lists the windows that can be centered.
+    List,
+
+    /// Centers the given windows on their monitors.
+    Center(Target),
+
+    /// Centers every window that can be centered.
+    CenterAll,
+
+    /// Lists the connected monitors.
+    Monitors,
+
+    /// Brings back windows left on coordinates no monitor covers anymore.
+    Rescue,
+
+    /// Keeps running in the background, opening the overlay and centering windows when the
+    /// hotkeys set in the preferences are pressed.
+    Daemon,
+
+    /// Renders a screenshot of the overlay on a made up desktop, for docs and bug reports.
+    Preview {
+        /// The desktop to draw the overlay on, as a YAML file.
+        desktop: String,
+        /// The horizontal position of the cursor on the desktop.
+        #[arg(allow_negative_numbers = true)]
+        x: i32,
+        /// The vertical position of the cursor on the desktop.
+        #[arg(allow_negative_numbers = true)]
+        y: i32,
+        /// Where the PNG image is saved.
+        output: String,
+    },
+
+    /// Lists the graphics adapters and the one the overlay would use, to diagnose rendering issues.
+    GpuInfo,
+}
+
+/// The windows a command applies to. Exactly one way of finding them has to be given.
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+struct Target {
+    /// The id of the window, as printed by the list command (decimal or hexadecimal).
+    #[arg(long, value_parser = parse_id)]
+    id: Option<isize>,
+
+    /// A regular expression matched against the titles of the windows.
+    #[arg(long)]
+    title: Option<Regex>,
+
+    /// The window in the foreground.
+    #[arg(long)]
+    active: bool,
+
+    /// The window under the cursor.
+    #[arg(long)]
+    under_cursor: bool,
+}
+
+/// A window as printed by the list command.
+#[derive(Debug, Serialize)]
+struct WindowEntry<'a> {
+    id: isize,
+    title: &'a str,
+    app: Option<&'a str>,
+    rect: Rect,
+    monitor: &'a str,
+    centered: bool,
+}
+
+/// What became of a window a command tried to move, as printed by the command.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum MoveResult {
+    Moved(WindowMove),
+    Failed(MoveFailure),
+}
+
+impl MoveResult {
+    /// Moves the window to its center, or only reports where it would go if it's a dry run.
+    fn center(window: &mut WindowInfo, dry_run: bool) -> Self {
+        match WindowMove::perform(window, dry_run, WindowInfo::center, WindowInfo::centered_rect) {
+            Ok(window_move) => Self::Moved(window_move),
+            Err(e) => {
+                log::error!("Failed to center \"{}\": {}", window.title(), e);
+                Self::Failed(MoveFailure::new(window, e.as_ref()))
+            }
+        }
+    }
+}
+
+impl From<Result<WindowMove, MoveFailure>> for MoveResult {
+    fn from(result: Result<WindowMove, MoveFailure>) -> Self {
+        match result {
+            Ok(window_move) => Self::Moved(window_move),
+            Err(failure) => Self::Failed(failure),
+        }
+    }
+}
+
+/// A monitor as printed by the monitors command.
+#[derive(Debug, Serialize)]
+struct MonitorEntry<'a> {
+    name: &'a str,
+    bounds: Rect,
+    work_area: Rect,
+}
+
 
 fn main() {
     init_logger().expect("Failed to initialize logger");
 
-    let event_loop = EventLoop::new().unwrap();
-    let mut app = OverlayApp::new();
-    
+    let cli = Cli::parse();
+
+    let Some(command) = cli.command else {
+        let event_loop = EventLoop::with_user_event().build().unwrap();
+        let mut app = OverlayApp::new();
+
+        if let Err(e) = listen_display_changes(event_loop.create_proxy()) {
+            log::warn!("Could not listen to display changes: {}", e);
+        }
+
+        event_loop.set_control_flow(ControlFlow::Wait);
+        event_loop.run_app(&mut app)
+            .unwrap();
+
+        return;
+    };
+
+    // The program has no console of its own, so commands print to the one they were run from.
+    #[cfg(target_os = "windows")]
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+
+    let result = match command {
+        Command::List => list(cli.json),
+        Command::Center(target) => center(&target, cli.dry_run, cli.json),
+        Command::CenterAll => center_all(cli.dry_run, cli.json),
+        Command::Monitors => monitors(cli.json),
+        Command::Rescue => rescue(cli.dry_run, cli.json),
+        Command::Daemon => daemon(),
+        Command::Preview { desktop, x, y, output } => preview(&desktop, PhysicalPosition::new(x, y), &output),
+        Command::GpuInfo => {
+            println!("{}", OverlayApp::gpu_info());
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        log::error!("The command failed: {}", e);
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Parses a window id, written either in decimal or in hexadecimal with a `0x` prefix.
+fn parse_id(id: &str) -> Result<isize, std::num::ParseIntError> {
+    match id.strip_prefix("0x") {
+        Some(hex) => isize::from_str_radix(hex, 16),
+        None => id.parse(),
+    }
+}
+
+fn list(json: bool) -> Result<(), Box<dyn Error>> {
+    let windows = get_windows()?;
+
+    let entries = windows
+        .iter()
+        .map(|w| WindowEntry {
+            id: w.id(),
+            title: w.title(),
+            app: w.app(),
+            rect: w.rect(),
+            monitor: w.monitor().name(),
+            centered: w.is_centered(),
+        })
+        .collect::<Vec<_>>();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!("{:<12} {:<8} {:<16} {:<28} TITLE", "ID", "CENTERED", "MONITOR", "RECT");
+
+    for entry in entries {
+        let rect = entry.rect;
+
+        println!(
+            "{:<12} {:<8} {:<16} {:<28} {}",
+            format!("{:#x}", entry.id),
+            if entry.centered { "yes" } else { "no" },
+            entry.monitor,
+            format!("({}, {}) [{}x{}]", rect.left, rect.top, rect.width(), rect.height()),
+            entry.title
+        );
+    }
+
+    Ok(())
+}
+
+fn center(target: &Target, dry_run: bool, json: bool) -> Result<(), Box<dyn Error>> {
+    let mut windows = get_windows()?;
+
+    let matches = |window: &WindowInfo| -> bool {
+        if let Some(id) = target.id {
+            return window.id() == id;
+        }
+
+        if let Some(title) = &target.title {
+            return title.is_match(window.title());
+        }
+
+        false
+    };
+
+    let mut targets = match (target.active, target.under_cursor) {
+        (true, _) => find_active_window(&mut windows).into_iter().collect(),
+        (_, true) => find_window_under_cursor(&mut windows).into_iter().collect(),
+        _ => windows.iter_mut().filter(|w| matches(w)).collect::<Vec<_>>(),
+    };
+
+    if targets.is_empty() {
+        return Err("no window matches the target".into());
+    }
+
+    let results = targets
+        .iter_mut()
+        .map(|w| MoveResult::center(w, dry_run))
+        .collect::<Vec<_>>();
+
+    report(&results, json)
+}
+
+fn center_all(dry_run: bool, json: bool) -> Result<(), Box<dyn Error>> {
+    let mut windows = get_windows()?;
+
+    let results = windows
+        .iter_mut()
+        .filter(|w| !w.is_centered())
+        .map(|w| MoveResult::center(w, dry_run))
+        .collect::<Vec<_>>();
+
+    report(&results, json)
+}
+
+fn monitors(json: bool) -> Result<(), Box<dyn Error>> {
+    let monitors = get_monitors()?;
+
+    let entries = monitors
+        .iter()
+        .map(|m| MonitorEntry { name: m.name(), bounds: m.bounds(), work_area: m.work_area() })
+        .collect::<Vec<_>>();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!("{:<16} {:<28} WORK AREA", "NAME", "BOUNDS");
+
+    let describe = |r: Rect| format!("({}, {}) [{}x{}]", r.left, r.top, r.width(), r.height());
+
+    for entry in entries {
+        println!("{:<16} {:<28} {}", entry.name, describe(entry.bounds), describe(entry.work_area));
+    }
+
+    Ok(())
+}
+
+fn rescue(dry_run: bool, json: bool) -> Result<(), Box<dyn Error>> {
+    let mut windows = get_windows()?;
+    let monitors = get_monitors()?;
+
+    let rescues = rescue_windows(&mut windows, &monitors, Preferences::get().rescue_threshold, dry_run);
+
+    if rescues.is_empty() && !json {
+        println!("No off-screen windows found.");
+        return Ok(());
+    }
+
+    report(&rescues.into_iter().map(MoveResult::from).collect::<Vec<_>>(), json)
+}
+
+fn daemon() -> Result<(), Box<dyn Error>> {
+    let event_loop = EventLoop::with_user_event().build()?;
+
+    if let Err(e) = listen_display_changes(event_loop.create_proxy()) {
+        log::warn!("Could not listen to display changes: {}", e);
+    }
+
+    let failures = listen_hotkeys(Preferences::get().hotkeys.bindings(), event_loop.create_proxy())?;
+
+    for failure in failures {
+        eprintln!("Warning: could not register {failure}");
+    }
+
+    let mut app = OverlayApp::resident();
+
     event_loop.set_control_flow(ControlFlow::Wait);
-    event_loop.run_app(&mut app)
-        .unwrap();
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
+
+/// Prints what became of every window a command tried to move. Windows that failed to move
+/// don't stop the rest, but fail the command once everything was printed.
+fn report(results: &[MoveResult], json: bool) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(results)?);
+    } else {
+        for result in results {
+            match result {
+                MoveResult::Moved(window_move) => println!("{window_move}"),
+                MoveResult::Failed(failure) => eprintln!("{failure}"),
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|r| matches!(r, MoveResult::Failed(_))).count();
+
+    if failed > 0 {
+        return Err(format!("{failed} of {} windows could not be moved", results.len()).into());
+    }
+
+    Ok(())
+}
+
+fn preview(desktop: &str, cursor: PhysicalPosition<i32>, output: &str) -> Result<(), Box<dyn Error>> {
+    let desktop = Desktop::try_from_file(desktop)?;
+
+    OverlayApp::render_preview(&desktop, cursor, Theme::get(), Preferences::get().renderer)?.save_png(output)?;
+
+    log::info!("Preview saved to \"{}\".", output);
+
+    Ok(())
 }
@@ -2,16 +2,21 @@ use pollster::FutureExt;
 use wgpu::util::DeviceExt;
 
 use super::*;
+use super::software::{self, SoftwareRenderer};
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use winit::{
     application::ApplicationHandler,
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{ElementState, MouseButton, WindowEvent},
-    event_loop::ActiveEventLoop,
+    event::{ElementState, KeyEvent, MouseButton, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow},
+    monitor::MonitorHandle,
+    keyboard::{Key, ModifiersState, NamedKey},
     window::{
         CursorIcon,
-        Icon,
         Window,
         WindowButtons,
         WindowId,
@@ -20,12 +25,69 @@ use winit::{
 };
 
 #[cfg(target_os = "windows")]
-use winit::platform::windows::{CornerPreference, IconExtWindows, WindowAttributesExtWindows};
+use winit::{
+    platform::windows::{CornerPreference, IconExtWindows, WindowAttributesExtWindows},
+    window::Icon,
+};
+
+
+/// What the overlay is told about from outside of its event loop.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverlayEvent {
+    /// A hotkey was pressed.
+    Hotkey(HotkeyAction),
+    /// Monitors were added, removed, rearranged or had their resolution changed.
+    DisplayChanged,
+}
 
 
 pub struct OverlayApp<'a> {
     state: Option<State<'a>>,
     windows: Vec<WindowInfo>,
+    /// The rects of the windows that are already centered, which are only outlined.
+    centered: Vec<Rect>,
+    monitors: Vec<MonitorInfo>,
+
+    modifiers: ModifiersState,
+    cursor: PhysicalPosition<i32>,
+    /// The point where a rubber-band (lasso) selection started, if one is in progress.
+    lasso_origin: Option<PhysicalPosition<i32>>,
+    /// The window being pressed on, which may be dragged onto another monitor.
+    drag: Option<Drag>,
+    /// The ids of the windows selected for a batch action.
+    selected: Vec<isize>,
+    /// The hint letters typed so far.
+    hint_input: String,
+    /// The text used to filter windows while in search mode, `None` when not searching.
+    search_query: Option<String>,
+    /// The rects the windows moved through the overlay had before each move, oldest first.
+    history: Vec<(isize, Rect)>,
+    /// The ids of the windows moved by the last nudge, which show their offset from the center.
+    nudged: Vec<isize>,
+    /// Whether the information panel of the hovered window is shown.
+    show_info: bool,
+    /// Whether every window and monitor is outlined.
+    show_outlines: bool,
+    /// Whether guides are drawn through the center of every monitor.
+    show_guides: bool,
+    /// Whether one of the overlay's windows lost the focus, which closes the overlay unless
+    /// another one of them got it.
+    focus_lost: bool,
+    /// When the windows are enumerated again, to keep up with the ones that changed meanwhile.
+    next_refresh: Instant,
+    /// The layout of the monitors the overlay's windows were created for.
+    layout: Vec<MonitorLayout>,
+    /// Whether the program keeps running in the background once the overlay is closed, to open
+    /// it again when its hotkey is pressed.
+    resident: bool,
+    /// Whether the overlay is open. Resident overlays keep their windows hidden while closed.
+    visible: bool,
+}
+
+impl Default for OverlayApp<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> OverlayApp<'a> {
@@ -33,58 +95,140 @@ impl<'a> OverlayApp<'a> {
         Self {
             state: None,
             windows: Vec::new(),
+            centered: Vec::new(),
+            monitors: Vec::new(),
+
+            modifiers: ModifiersState::empty(),
+            cursor: PhysicalPosition::new(0, 0),
+            lasso_origin: None,
+            drag: None,
+            selected: Vec::new(),
+            hint_input: String::new(),
+            search_query: None,
+            history: Vec::new(),
+            nudged: Vec::new(),
+            show_info: Preferences::get().show_info,
+            show_outlines: Preferences::get().show_outlines,
+            show_guides: Preferences::get().show_guides,
+            focus_lost: false,
+            next_refresh: Instant::now(),
+            layout: Vec::new(),
+            resident: false,
+            visible: false,
+        }
+    }
+
+    /// Creates an overlay that keeps running in the background once closed, and is opened by the
+    /// hotkeys sent to its event loop. Its windows are created up front, so opening it is instant.
+    pub fn resident() -> Self {
+        Self {
+            resident: true,
+            ..Self::new()
+        }
+    }
+
+    /// Renders a single frame of the overlay offscreen, as it would look on the given desktop
+    /// with the cursor at the given position (in desktop coordinates) and the given theme, using
+    /// the given renderer.
+    pub fn render_preview(
+        desktop: &Desktop,
+        cursor: PhysicalPosition<i32>,
+        theme: &Theme,
+        renderer: RendererKind,
+    ) -> Result<Frame, Box<dyn Error>> {
+        let rect = desktop.bounds();
+        let mut state = State::offscreen(rect, desktop.scale_factor, theme.clone(), renderer)?;
+        let mut app = Self::new();
+
+        app.windows = desktop.windows();
+        app.monitors = desktop.monitors();
+        app.cursor = PhysicalPosition::new(cursor.x - rect.left, cursor.y - rect.top);
+        app.sync(&mut state);
+
+        state.render_offscreen()
+    }
+
+    /// Describes every graphics adapter available with the backend in the preferences, and the
+    /// one the overlay would pick.
+    pub fn gpu_info() -> String {
+        let preferences = Preferences::get();
+        let instance = Gpu::create_instance();
+        let mut lines = vec![
+            format!("Backend: {:?}", preferences.backend),
+            format!("Power preference: {:?}", preferences.power_preference),
+            format!("Renderer: {:?}", preferences.renderer),
+            String::new(),
+            "Adapters:".to_string(),
+        ];
+
+        let adapters = instance.enumerate_adapters(Gpu::backends(preferences.backend));
+
+        if adapters.is_empty() {
+            lines.push("  None, the overlay is drawn on the CPU.".to_string());
+        }
+
+        for adapter in &adapters {
+            lines.push(format!("  {}", Gpu::describe_adapter(adapter)));
+        }
+
+        // The window's surface may rule out some adapters, which can't be known without it.
+        if let Some(adapter) = Gpu::create_adapter(&instance, None, false) {
+            lines.push(String::new());
+            lines.push(format!("Preferred: {}", Gpu::describe_adapter(&adapter)));
         }
+
+        lines.join("\n")
     }
 }
 
-impl<'a> ApplicationHandler for OverlayApp<'a> {
+impl<'a> ApplicationHandler<OverlayEvent> for OverlayApp<'a> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let windows = get_windows().unwrap_or_else(|e| {
-            panic!("Could not enumerate application windows: {e}");
-        });
+        if !self.resident {
+            self.show(event_loop);
+            return;
+        }
 
-        // Loads the application's icon, if possible.
-        let icon = match Icon::from_path("icon.ico", None) {
-            Ok(i) => Some(i),
-            Err(e) => {
-                log::warn!("Could not load icon: {}", e);
-                None
+        if self.state.is_none() {
+            self.state = Self::create_state(event_loop, false);
+
+            if self.state.is_none() {
+                event_loop.exit();
+                return;
+            }
+
+            self.layout = Self::monitor_layout(event_loop);
+
+            log::info!("Running in the background, waiting for hotkeys.");
+        }
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: OverlayEvent) {
+        let action = match event {
+            OverlayEvent::Hotkey(action) => action,
+            OverlayEvent::DisplayChanged => {
+                self.handle_display_change(event_loop);
+                return;
             }
         };
-        
-        let (position, size) = Self::calculate_display_area(&event_loop);
 
-        let window_attributes = Window::default_attributes()
-            .with_active(true)
-            .with_content_protected(true)
-            .with_decorations(false)
-            .with_enabled_buttons(WindowButtons::empty())
-            .with_inner_size(size)
-            .with_position(position)
-            .with_resizable(false)
-            .with_title("Centered Windows")
-            .with_transparent(true)
-            .with_window_level(WindowLevel::AlwaysOnTop);
+        log::debug!("Hotkey pressed to {}.", action);
 
-        // Specific window settings on Windows OS.
-        #[cfg(target_os = "windows")]
-        let window_attributes = window_attributes
-            .with_corner_preference(CornerPreference::DoNotRound)
-            .with_drag_and_drop(false)
-            .with_taskbar_icon(icon);
+        if action == HotkeyAction::ShowOverlay {
+            match self.state.as_mut() {
+                // Pressing the hotkey again closes the overlay.
+                Some(state) if self.visible => state.close(),
+                _ => self.show(event_loop),
+            }
 
-        let window = event_loop.create_window(window_attributes).unwrap();
-        
-        log::debug!(
-            "Overlay window with position ({}, {}) and size [{}x{}] created.",
-            position.x,
-            position.y,
-            size.width,
-            size.height
-        );
-        
-        self.state = Some(State::new(window));
-        self.windows = windows;
+            return;
+        }
+
+        if let Err(e) = action.center() {
+            log::error!("Could not {}: {}", action, e);
+        }
+
+        // The open overlay catches up with the moved windows right away.
+        self.next_refresh = Instant::now();
     }
 
     fn window_event(
@@ -93,117 +237,55 @@ impl<'a> ApplicationHandler for OverlayApp<'a> {
         window_id: WindowId,
         event: WindowEvent
     ) {
-        let state = self.state
-            .as_mut()
-            .unwrap();
-        
-        let window = state.window();
-
-        if window.id() != window_id {
+        // The state is taken out of the app while handling the event, so that both can be
+        // modified independently.
+        let Some(mut state) = self.state.take() else {
             return;
-        }
+        };
 
-        match event {
-            WindowEvent::CloseRequested |
-            WindowEvent::KeyboardInput { .. } => {
-                event_loop.exit();
-            }
+        if let Some(index) = state.viewport_index(window_id) {
+            let scene = state.scene.clone();
+            let resized = matches!(event, WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. });
 
-            WindowEvent::Resized(physical_size) => {
-                state.resize(physical_size);
-            }
+            self.handle_window_event(&mut state, index, event_loop, event);
 
-            WindowEvent::Focused(has_focus) => {
-                if has_focus == false {
-                    event_loop.exit();
-                }
+            // Nothing is drawn continuously, so a frame is only requested when something changed.
+            if resized || state.scene != scene {
+                state.request_redraw();
             }
-            
-            WindowEvent::CursorMoved { position, .. } => {
-                let overlay_rect = state.rect();
-                let clip = state.clip.unwrap_or(Rect::default());
-                
-                window.set_cursor(match state.clip {
-                    Some(_) => CursorIcon::Pointer,
-                    None => CursorIcon::Default,
-                });
+        }
 
-                // If there is already a defined clip in the state, check if the same clip contains
-                // the cursor coordinates to avoid fetching the same window rect multiple times.
-                // If the clip is None the contains function will return false.
-                
-                if !clip.contains(position.x as i32, position.y as i32) {
-                    let clip = self.windows
-                        .iter()
-                        .map(|w| Rect::adjust(w.rect(), overlay_rect))
-                        .find(|r| r.contains(position.x as i32, position.y as i32));
+        self.state = Some(state);
+    }
 
-                    state.clip = clip;
-                }
-            }
-            
-            WindowEvent::MouseInput {
-                state: ElementState::Pressed,
-                button: MouseButton::Left,
-                ..
-            } => {
-                // The click event will only be processed if the cursor is over a window that can
-                // be centered, a.k.a. a clip is defined. It will then remove said window from the
-                // available windows list.
-                
-                if let Some(clip) = state.clip {
-                    let overlay_rect = state.rect();
-                    
-                    let found_window = self.windows
-                        .iter()
-                        .enumerate()
-                        .find(|(_, w)| {
-                            let rect = Rect::adjust(w.rect(), overlay_rect);
-                            rect == clip
-                        });
-                    
-                    if let Some((index, app_window)) = found_window {
-                        app_window.center()
-                            .expect("Could not center the window");
-                        
-                        self.windows.remove(index);
-                        
-                        window.set_cursor(CursorIcon::Default);
-                        state.clip = None;
-                    }
-                }
-            }
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Nothing is kept up to date while the overlay is closed.
+        if !self.visible {
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
 
-            WindowEvent::RedrawRequested => {
-                match state.render() {
-                    Ok(_) => {}
+        let Some(mut state) = self.state.take() else {
+            return;
+        };
 
-                    Err(wgpu::SurfaceError::Lost |
-                        wgpu::SurfaceError::Outdated) => {
-                        state.resize(state.size)
-                    }
+        if std::mem::take(&mut self.focus_lost) && !state.has_focus() {
+            state.close();
+        }
 
-                    Err(wgpu::SurfaceError::OutOfMemory) => {
-                        log::error!("Out of memory!");
-                        event_loop.exit()
-                    }
+        if Instant::now() >= self.next_refresh && !state.animation.is_closing() {
+            let scene = state.scene.clone();
 
-                    Err(wgpu::SurfaceError::Timeout) => {
-                        log::warn!("Surface Timeout!");
-                    }
-                }
-            }
+            self.refresh_windows(&mut state);
 
-            _ => {}
+            if state.scene != scene {
+                state.request_redraw();
+            }
         }
-    }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        self.state
-            .as_ref()
-            .unwrap()
-            .window()
-            .request_redraw();
+        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_refresh));
+
+        self.state = Some(state);
     }
 
     fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
@@ -212,217 +294,2441 @@ impl<'a> ApplicationHandler for OverlayApp<'a> {
 }
 
 impl<'a> OverlayApp<'a> {
-    /**
-    This function calculates the display area for the overlay window to be rendered on.
+    /// Opens the overlay on the windows and monitors as they are now. The overlay's windows are
+    /// created, or shown again if they're kept in the background.
+    fn show(&mut self, event_loop: &ActiveEventLoop) {
+        let enumerated = get_windows().and_then(|windows| Ok((windows, get_monitors()?)));
 
-    It will return the position and size of the overlay window.
-    */
-    pub fn calculate_display_area(
-        event_loop: &ActiveEventLoop
-    ) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
-        // `min_x` and `max_y` track the position of where the overlay should be rendered.
-        // This is typically the top-left coordinate across all monitors.
-        // `max_x` and `max_y` track the largest `x + width` and `y + height` values,
-        // which determines where the bottom-right corner across all monitors is.
+        let (mut windows, monitors) = match enumerated {
+            Ok(e) => e,
+            Err(e) => {
+                log::error!("Could not enumerate application windows and monitors: {}", e);
 
-        let mut min_x = 0;
-        let mut min_y = 0;
-        let mut max_x = 0;
-        let mut max_y = 0;
+                if !self.resident {
+                    event_loop.exit();
+                }
+
+                return;
+            }
+        };
 
-        for monitor in event_loop.available_monitors() {
-            let size = monitor.size();
-            let position = monitor.position();
+        // Off-screen windows can't be shown by the overlay, so they're brought back first.
+        let preferences = Preferences::get();
 
-            min_x = min_x.min(position.x);
-            min_y = min_y.min(position.y);
-            max_x = max_x.max(position.x + (size.width as i32));
-            max_y = max_y.max(position.y + (size.height as i32));
+        if preferences.rescue_on_open {
+            rescue_windows(&mut windows, &monitors, preferences.rescue_threshold, false);
         }
 
-        // The difference between the top-left and bottom-right corner yields the resulting rect to be display.
+        let state = match self.state.take() {
+            Some(state) if self.layout == Self::monitor_layout(event_loop) => Some(state),
+            Some(state) => self.rebuild(event_loop, state),
+            None => Self::create_state(event_loop, true),
+        };
 
-        (
-            PhysicalPosition::new(min_x, min_y),
-            PhysicalSize::new((max_x - min_x) as u32, (max_y - min_y) as u32)
-        )
+        // Resident overlays try again the next time they're opened.
+        let Some(mut state) = state else {
+            if !self.resident {
+                event_loop.exit();
+            }
+
+            return;
+        };
+
+        state.scene = Scene::default();
+        state.animation = Animation::new();
+        state.show();
+
+        self.windows = windows;
+        self.centered = Self::centered_rects();
+        self.monitors = monitors;
+        self.layout = Self::monitor_layout(event_loop);
+        self.next_refresh = Instant::now() + WINDOW_REFRESH_INTERVAL;
+        self.focus_lost = false;
+        self.visible = true;
+        self.sync(&mut state);
+
+        state.request_redraw();
+
+        self.state = Some(state);
     }
-}
 
+    /// Creates the overlay's windows again if the monitors they were created for changed. Closed
+    /// overlays are left as they are, since they're checked again once opened.
+    ///
+    /// If the new windows can't be created, a resident overlay is closed and tries again once
+    /// opened, while any other one exits.
+    fn handle_display_change(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.visible || self.layout == Self::monitor_layout(event_loop) {
+            return;
+        }
 
-/**
-Defines the input model for a Vertex in the shader.
- */
-#[repr(C)]
-#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Vertex {
-    position: [f32; 2],
-}
-
-impl Vertex {
-    fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: size_of::<Self>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    // No offset because this is the first (and only) attribute.
-                    offset: 0,
-                    // Corresponds to @location(0) in the shader code.
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x2,
-                }
-            ],
+        let Some(state) = self.state.take() else {
+            return;
+        };
+
+        self.state = self.rebuild(event_loop, state);
+
+        // The windows hold on to the monitors they were on, which changed as well.
+        if let Some(mut state) = self.state.take() {
+            self.refresh_windows(&mut state);
+            self.state = Some(state);
+            return;
+        }
+
+        if !self.resident {
+            event_loop.exit();
+            return;
         }
+
+        self.forget_session();
     }
-}
 
+    /// Closes the overlay once it's faded out. Resident overlays only hide their windows and
+    /// forget what was going on, ready to be opened again.
+    fn dismiss(&mut self, event_loop: &ActiveEventLoop, state: &mut State) {
+        if !self.resident {
+            event_loop.exit();
+            return;
+        }
 
-// These indices will always make a quad where the initial vertex is the top-right point. 
-const INDICES: &[u16] = &[0, 1, 2, 0, 2, 3];
+        state.hide();
 
+        self.forget_session();
+    }
 
-struct State<'a> {
-    size: PhysicalSize<u32>,
+    /// Forgets what was going on while the overlay was open, once it's closed.
+    fn forget_session(&mut self) {
+        self.visible = false;
+        self.lasso_origin = None;
+        self.drag = None;
+        self.selected.clear();
+        self.hint_input.clear();
+        self.search_query = None;
+        self.nudged.clear();
+    }
 
-    surface: wgpu::Surface<'a>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    render_pipeline: wgpu::RenderPipeline,
-    clip: Option<Rect>,
+    fn handle_window_event(
+        &mut self,
+        state: &mut State,
+        index: usize,
+        event_loop: &ActiveEventLoop,
+        event: WindowEvent,
+    ) {
+        // Input is ignored while the overlay fades out.
+        if state.animation.is_closing() && !matches!(
+            event,
+            WindowEvent::CloseRequested
+                | WindowEvent::Resized(_)
+                | WindowEvent::ScaleFactorChanged { .. }
+                | WindowEvent::RedrawRequested
+        ) {
+            return;
+        }
 
-    window: Arc<Window>,
-}
+        match event {
+            WindowEvent::CloseRequested => {
+                self.dismiss(event_loop, state);
+            }
 
+            WindowEvent::KeyboardInput {
+                event: KeyEvent { logical_key, state: ElementState::Pressed, .. },
+                ..
+            } => {
+                // An open menu is closed before anything else.
+                if state.scene.menu.is_some() && logical_key == Key::Named(NamedKey::Escape) {
+                    state.scene.menu = None;
+                    self.sync(state);
 
-impl<'a> State<'a> {
-    pub fn new(window: Window) -> Self {
-        let window_arc = Arc::new(window);
-        let size = window_arc.inner_size();
+                    return;
+                }
 
-        let instance = Self::create_instance();
-        let surface = instance.create_surface(window_arc.clone()).unwrap();
-        let adapter = Self::create_adapter(instance, &surface);
-        let (device, queue) = Self::create_device(&adapter);
-        let surface_capabilities = surface.get_capabilities(&adapter);
-        let config = Self::create_surface_config(size, surface_capabilities);
+                // While searching, every key is used to edit the query.
+                if let Some(query) = self.search_query.as_mut() {
+                    match logical_key {
+                        Key::Named(NamedKey::Escape) => {
+                            self.search_query = None;
+                        }
+
+                        // Centers the window that best matches the query and leaves search mode.
+                        Key::Named(NamedKey::Enter) => {
+                            if let Some(index) = Self::best_match(&self.windows, query) {
+                                self.search_query = None;
+                                self.move_window(index, WindowInfo::center);
+                            }
+                        }
+
+                        Key::Named(NamedKey::Backspace) => {
+                            query.pop();
+                        }
+
+                        Key::Named(NamedKey::Space) => {
+                            query.push(' ');
+                        }
+
+                        Key::Character(c) => {
+                            query.push_str(&c);
+                        }
+
+                        _ => {}
+                    }
 
-        surface.configure(&device, &config);
+                    self.sync(state);
 
-        let render_pipeline = Self::create_render_pipeline(&device, &config);
-        
-        log::debug!("Render pipeline created and ready to use.");
+                    return;
+                }
 
-        Self {
-            size,
+                // Only consecutive nudges keep showing the offset from the center. Modifiers are
+                // ignored since they change the size of a nudge.
+                let is_nudge = matches!(
+                    logical_key,
+                    Key::Named(
+                        NamedKey::ArrowLeft | NamedKey::ArrowRight | NamedKey::ArrowUp | NamedKey::ArrowDown |
+                        NamedKey::Control | NamedKey::Shift
+                    )
+                );
 
-            surface,
-            device,
-            queue,
-            config,
-            render_pipeline,
-            clip: None,
+                if !is_nudge {
+                    self.nudged.clear();
+                }
 
-            window: window_arc,
-        }
-    }
+                match logical_key {
+                    // Arrow keys nudge the selected windows (or the hovered one) by a pixel.
+                    // Holding shift or control nudges them by a larger step.
+                    Key::Named(key @ (NamedKey::ArrowLeft | NamedKey::ArrowRight | NamedKey::ArrowUp | NamedKey::ArrowDown)) => {
+                        let step = if self.modifiers.control_key() {
+                            NUDGE_LARGE
+                        } else if self.modifiers.shift_key() {
+                            NUDGE_MEDIUM
+                        } else {
+                            NUDGE_SMALL
+                        };
+
+                        let (x, y) = match key {
+                            NamedKey::ArrowLeft => (-step, 0),
+                            NamedKey::ArrowRight => (step, 0),
+                            NamedKey::ArrowUp => (0, -step),
+                            _ => (0, step),
+                        };
+
+                        self.nudge(state, x, y);
+                    }
 
-    fn create_instance() -> wgpu::Instance {
-        wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        })
-    }
+                    // F1 toggles the information panel of the hovered window.
+                    Key::Named(NamedKey::F1) => {
+                        self.show_info = !self.show_info;
+                    }
 
-    fn create_adapter(instance: wgpu::Instance, surface: &wgpu::Surface) -> wgpu::Adapter {
-        instance
-            .request_adapter(
-                &wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
+                    // F2 toggles the outlines of every window and monitor.
+                    Key::Named(NamedKey::F2) => {
+                        self.show_outlines = !self.show_outlines;
+                    }
+
+                    // F3 toggles the guides through the center of every monitor.
+                    Key::Named(NamedKey::F3) => {
+                        self.show_guides = !self.show_guides;
+                    }
+
+                    // Control + Z undoes the last move, whichever window it was.
+                    Key::Character(c) if self.modifiers.control_key() && c.eq_ignore_ascii_case("z") => {
+                        self.undo(None);
+                    }
+
+                    // Centers every selected window. Holding shift centers them as a group,
+                    // keeping the offsets between them, instead of each on its own monitor.
+                    Key::Named(NamedKey::Enter) if !self.selected.is_empty() => {
+                        let offset = match self.modifiers.shift_key() {
+                            true => {
+                                let selected = self.windows
+                                    .iter_mut()
+                                    .filter(|w| self.selected.contains(&w.id()))
+                                    .collect::<Vec<_>>();
+
+                                group_offset(&selected)
+                            }
+                            false => None,
+                        };
+
+                        let indices = (0..self.windows.len())
+                            .filter(|i| self.selected.contains(&self.windows[*i].id()))
+                            .collect::<Vec<_>>();
+
+                        // Every window is moved on its own, so one that fails doesn't hold back
+                        // the rest and only the ones that moved can be undone.
+                        for index in indices {
+                            match offset {
+                                Some(offset) => self.move_window(index, |w| {
+                                    let position = w.position();
+
+                                    w.move_to(PhysicalPosition::new(position.x + offset.x, position.y + offset.y))
+                                }),
+                                None => self.move_window(index, WindowInfo::center),
+                            }
+                        }
+
+                        self.selected.clear();
+                    }
+
+                    // Escape discards the typed hint letters and the current selection before
+                    // closing the overlay.
+                    Key::Named(NamedKey::Escape) if !self.hint_input.is_empty() => {
+                        self.hint_input.clear();
+                    }
+
+                    Key::Named(NamedKey::Escape) if !self.selected.is_empty() => {
+                        self.selected.clear();
+                    }
+
+                    Key::Named(NamedKey::Backspace) => {
+                        self.hint_input.pop();
+                    }
+
+                    // A slash enters search mode, where typing filters the windows by their title
+                    // and application name.
+                    Key::Character(c) if c == "/" => {
+                        self.search_query = Some(String::new());
+                        self.hint_input.clear();
+                    }
+
+                    // Typing the letters of a hint centers the window it's drawn over.
+                    Key::Character(c) if Preferences::get().show_hints && is_hint_input(&c) => {
+                        self.hint_input.push_str(&c.to_lowercase());
+
+                        let labels = hint_labels(self.windows.len());
+                        let found = labels.iter().position(|l| *l == self.hint_input);
+
+                        if let Some(index) = found {
+                            self.hint_input.clear();
+                            self.move_window(index, WindowInfo::center);
+                        } else if !labels.iter().any(|l| l.starts_with(&self.hint_input)) {
+                            // Nothing can be matched anymore, so the hint is typed from the start.
+                            self.hint_input.clear();
+                        }
+                    }
+
+                    // Modifier keys are only used alongside other input.
+                    Key::Named(NamedKey::Control | NamedKey::Shift) => {}
+
+                    _ => state.close(),
                 }
-            )
-            .block_on()
-            .unwrap()
+
+                self.sync(state);
+            }
+
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+
+            WindowEvent::Resized(physical_size) => {
+                state.resize(index, physical_size);
+            }
+
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                log::info!("The scale factor of a monitor changed to {}.", scale_factor);
+                state.set_scale_factor(index, scale_factor);
+            }
+
+            WindowEvent::Focused(has_focus) => {
+                // Focus moving between the overlay's own windows doesn't close it, which is only
+                // known once every window had its say.
+                self.focus_lost = !has_focus;
+            }
+            
+            WindowEvent::CursorMoved { position, .. } => {
+                let overlay_rect = state.rect();
+                let clip = state.scene.clip.unwrap_or_default();
+
+                // The position is relative to the viewport's window, which the overlay is made of.
+                let viewport = state.viewports[index].rect;
+
+                self.cursor = PhysicalPosition::new(
+                    viewport.left + position.x as i32,
+                    viewport.top + position.y as i32,
+                );
+
+                if let Some(menu) = state.scene.menu.as_mut() {
+                    menu.hovered = menu.items
+                        .iter()
+                        .position(|(_, r)| r.contains(self.cursor.x, self.cursor.y));
+                }
+
+                // If there is already a defined clip in the state, check if the same clip contains
+                // the cursor coordinates to avoid fetching the same window rect multiple times.
+                // If the clip is None the contains function will return false.
+                
+                if !clip.contains(self.cursor.x, self.cursor.y) {
+                    let hovered = self.windows
+                        .iter()
+                        .find(|w| {
+                            Rect::adjust(w.rect(), overlay_rect).contains(self.cursor.x, self.cursor.y)
+                        });
+
+                    state.hover(hovered);
+                }
+
+                // The information panel follows the cursor, and the guides follow the hovered
+                // window.
+                self.update_info(state);
+                self.update_guides(state);
+
+                // Once the cursor moves far enough from where a window was pressed, the window is
+                // being dragged.
+                if let Some(drag) = self.drag.as_mut() {
+                    let distance = (self.cursor.x - drag.origin.x).abs()
+                        .max((self.cursor.y - drag.origin.y).abs());
+
+                    drag.dragging |= distance >= DRAG_THRESHOLD;
+
+                    self.sync(state);
+                }
+
+                // While dragging a lasso, every window it touches becomes selected.
+                if let Some(origin) = self.lasso_origin {
+                    let lasso = Rect::from_points(origin, self.cursor);
+
+                    self.selected = self.windows
+                        .iter()
+                        .filter(|w| Rect::adjust(w.rect(), overlay_rect).intersects(&lasso))
+                        .map(|w| w.id())
+                        .collect();
+
+                    state.scene.lasso = Some(lasso);
+                    self.sync(state);
+                }
+
+                self.update_cursor(state);
+            }
+            
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                // While a menu is open, a click either picks one of its actions or dismisses it.
+                if let Some(menu) = state.scene.menu.take() {
+                    if let Some(index) = menu.hovered {
+                        self.perform(menu.items[index].0.clone(), menu.window);
+                    }
+
+                    self.sync(state);
+
+                    return;
+                }
+
+                let hovered = self.hovered_window(state);
+
+                // Holding control or shift toggles the hovered window in the selection instead of
+                // centering it right away.
+                if self.modifiers.control_key() || self.modifiers.shift_key() {
+                    if let Some(index) = hovered {
+                        let id = self.windows[index].id();
+
+                        match self.selected.iter().position(|s| *s == id) {
+                            Some(position) => { self.selected.remove(position); }
+                            None => self.selected.push(id),
+                        }
+
+                        self.sync(state);
+                    }
+
+                    return;
+                }
+
+                // The click event will only be processed if the cursor is over a window that can
+                // be centered, a.k.a. a clip is defined. The window is centered once the button
+                // is released, unless it has been dragged onto another monitor by then.
+                // Clicking anywhere else starts a lasso selection.
+                
+                match hovered {
+                    Some(index) => {
+                        self.drag = Some(Drag {
+                            window: self.windows[index].id(),
+                            origin: self.cursor,
+                            dragging: false,
+                        });
+                    }
+
+                    None => {
+                        self.lasso_origin = Some(self.cursor);
+                    }
+                }
+            }
+
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => {
+                if let Some(drag) = self.drag.take() {
+                    let index = self.windows.iter().position(|w| w.id() == drag.window);
+
+                    match (index, drag.dragging) {
+                        (Some(index), false) => {
+                            self.move_window(index, WindowInfo::center);
+                        }
+
+                        // Dropping a window outside of every monitor cancels the drag.
+                        (Some(index), true) => {
+                            if let Some(monitor) = self.monitor_under_cursor(state) {
+                                let monitor = self.monitors[monitor].clone();
+
+                                self.move_window(index, |w| w.drop_on(monitor));
+                            }
+                        }
+
+                        (None, _) => {}
+                    }
+                }
+
+                self.lasso_origin = None;
+                state.scene.lasso = None;
+
+                self.sync(state);
+            }
+
+            // Right-clicking a window opens a menu with every action that can be performed on it.
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Right,
+                ..
+            } => {
+                state.scene.menu = None;
+
+                if let Some(index) = self.hovered_window(state) {
+                    let window = &self.windows[index];
+                    let actions = self.menu_actions(window);
+
+                    state.open_menu(self.cursor, window.id(), actions);
+                }
+
+                self.update_cursor(state);
+            }
+
+            WindowEvent::RedrawRequested => {
+                // Frames that fail are retried, so there's always a frame to come.
+                if !state.redraw(index) {
+                    return;
+                }
+
+                // Frames keep coming while the overlay is animated, until it's fully closed.
+                if state.animation.is_closed() {
+                    self.dismiss(event_loop, state);
+                } else if state.animation.is_running() {
+                    state.request_redraw();
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Hands everything that depends on the windows (hover, selection, hints and search results)
+    /// to the state, so that it reflects the latest changes.
+    fn sync(&self, state: &mut State) {
+        let overlay_rect = state.rect();
+
+        let hovered = self.hovered_window(state).map(|index| &self.windows[index]);
+        state.hover(hovered);
+
+        state.scene.selection = self.windows
+            .iter()
+            .filter(|w| self.selected.contains(&w.id()))
+            .map(|w| Rect::adjust(w.rect(), overlay_rect))
+            .collect();
+
+        Self::update_search(state, &self.windows, self.search_query.as_deref());
+
+        match self.search_query {
+            Some(_) => state.scene.hints.clear(),
+            None => Self::update_hints(state, &self.windows, &self.hint_input),
+        }
+
+        state.scene.offsets = self.windows
+            .iter()
+            .filter(|w| self.nudged.contains(&w.id()))
+            .map(|w| {
+                let (x, y) = w.center_offset();
+                let text = match (x, y) {
+                    (0, 0) => String::from("Centered"),
+                    _ => format!("{x:+}, {y:+} from center"),
+                };
+
+                (Rect::adjust(w.rect(), overlay_rect), text)
+            })
+            .collect();
+
+        self.update_outlines(state);
+        self.update_guides(state);
+        self.update_info(state);
+        self.update_drag(state);
+        self.update_cursor(state);
+    }
+
+    /// Hands the outlines of every window and monitor to the state.
+    fn update_outlines(&self, state: &mut State) {
+        state.scene.outlines.clear();
+        state.scene.monitor_outlines.clear();
+
+        if !self.show_outlines {
+            return;
+        }
+
+        let overlay_rect = state.rect();
+
+        let candidates = self.windows.iter().map(|w| (w.rect(), w.is_centered()));
+        let centered = self.centered.iter().map(|r| (*r, true));
+
+        state.scene.outlines = candidates
+            .chain(centered)
+            .map(|(rect, centered)| (Rect::adjust(rect, overlay_rect), centered))
+            .collect();
+
+        state.scene.monitor_outlines = self.monitors
+            .iter()
+            .enumerate()
+            .map(|(index, m)| MonitorOutline {
+                bounds: Rect::adjust(m.bounds(), overlay_rect),
+                work_area: Rect::adjust(m.work_area(), overlay_rect),
+                label: format!("{}: {}", index + 1, m.name().trim_start_matches("\\\\.\\")),
+            })
+            .collect();
+    }
+
+    /// Hands a vertical and a horizontal guide through the center of every monitor's area to the
+    /// state. This is the same area windows are centered on. A guide is lit when the center of the
+    /// hovered window lines up with it.
+    fn update_guides(&self, state: &mut State) {
+        state.scene.guides.clear();
+
+        if !self.show_guides {
+            return;
+        }
+
+        let overlay_rect = state.rect();
+
+        let hovered_center = self.hovered_window(state).map(|index| {
+            let rect = self.windows[index].rect();
+
+            (rect.left + (rect.width() / 2) as i32, rect.top + (rect.height() / 2) as i32)
+        });
+
+        for monitor in &self.monitors {
+            let area = monitor.rect();
+            let center_x = area.left + (area.width() / 2) as i32;
+            let center_y = area.top + (area.height() / 2) as i32;
+
+            let vertical = Rect {
+                left: center_x - GUIDE_THICKNESS / 2,
+                top: area.top,
+                right: center_x - GUIDE_THICKNESS / 2 + GUIDE_THICKNESS,
+                bottom: area.bottom,
+            };
+
+            let horizontal = Rect {
+                left: area.left,
+                top: center_y - GUIDE_THICKNESS / 2,
+                right: area.right,
+                bottom: center_y - GUIDE_THICKNESS / 2 + GUIDE_THICKNESS,
+            };
+
+            state.scene.guides.extend([
+                (Rect::adjust(vertical, overlay_rect), hovered_center.is_some_and(|(x, _)| x == center_x)),
+                (Rect::adjust(horizontal, overlay_rect), hovered_center.is_some_and(|(_, y)| y == center_y)),
+            ]);
+        }
+    }
+
+    /// Hands the information panel of the hovered window to the state, placed next to the cursor.
+    fn update_info(&self, state: &mut State) {
+        state.scene.info = None;
+
+        if !self.show_info {
+            return;
+        }
+
+        let Some(window) = self.hovered_window(state).map(|index| &self.windows[index]) else {
+            return;
+        };
+
+        let position = window.position();
+        let size = window.size();
+        let monitor = window.monitor().rect();
+        let (offset_x, offset_y) = window.center_offset();
+
+        let monitor_index = self.monitors
+            .iter()
+            .position(|m| m.rect() == monitor)
+            .map_or(String::from("?"), |index| (index + 1).to_string());
+
+        let lines = vec![
+            format!("Title: {}", window.title().chars().take(INFO_TITLE_LENGTH).collect::<String>()),
+            match window.app() {
+                Some(app) => format!("Application: {} ({})", app, window.class()),
+                None => format!("Class: {}", window.class()),
+            },
+            format!("Size: {} x {}", size.width, size.height),
+            format!("Position: {}, {}", position.x, position.y),
+            format!(
+                "Monitor: {} ({} x {} at {}, {})",
+                monitor_index,
+                monitor.width(),
+                monitor.height(),
+                monitor.left,
+                monitor.top,
+            ),
+            format!("Offset from center: {offset_x:+}, {offset_y:+}"),
+        ];
+
+        let position = PhysicalPosition::new(self.cursor.x + INFO_CURSOR_OFFSET, self.cursor.y + INFO_CURSOR_OFFSET);
+
+        state.scene.info = Some((position, lines));
+    }
+
+    /// Hands the proxy of the dragged window to the state, alongside the monitor it would be
+    /// dropped on and where it would end up.
+    fn update_drag(&self, state: &mut State) {
+        state.scene.drag = None;
+
+        let Some(drag) = self.drag.as_ref().filter(|d| d.dragging) else {
+            return;
+        };
+
+        let Some(window) = self.windows.iter().find(|w| w.id() == drag.window) else {
+            return;
+        };
+
+        let overlay_rect = state.rect();
+        let rect = Rect::adjust(window.rect(), overlay_rect);
+        let (offset_x, offset_y) = (self.cursor.x - drag.origin.x, self.cursor.y - drag.origin.y);
+
+        let proxy = Rect {
+            left: rect.left + offset_x,
+            top: rect.top + offset_y,
+            right: rect.right + offset_x,
+            bottom: rect.bottom + offset_y,
+        };
+
+        let target = self.monitor_under_cursor(state).map(|index| {
+            let monitor = &self.monitors[index];
+
+            (
+                Rect::adjust(monitor.rect(), overlay_rect),
+                Rect::adjust(window.dropped_rect_on(monitor), overlay_rect),
+            )
+        });
+
+        state.scene.preview = target.map(|(_, destination)| destination);
+        state.scene.drag = Some((proxy, target.map(|(monitor, _)| monitor)));
+    }
+
+    /// Finds the index of the monitor under the cursor, anywhere on it (the taskbar included).
+    fn monitor_under_cursor(&self, state: &State) -> Option<usize> {
+        let overlay_rect = state.rect();
+
+        self.monitors
+            .iter()
+            .position(|m| Rect::adjust(m.bounds(), overlay_rect).contains(self.cursor.x, self.cursor.y))
+    }
+
+    /// Shows a pointer whenever the cursor is over something that can be clicked.
+    fn update_cursor(&self, state: &State) {
+        let over_menu_item = state.scene.menu
+            .as_ref()
+            .is_some_and(|m| m.hovered.is_some());
+
+        state.set_cursor(match state.scene.clip.is_some() || over_menu_item {
+            true => CursorIcon::Pointer,
+            false => CursorIcon::Default,
+        });
+    }
+
+    /// Finds the index of the window under the cursor.
+    fn hovered_window(&self, state: &State) -> Option<usize> {
+        let overlay_rect = state.rect();
+
+        self.windows
+            .iter()
+            .position(|w| Rect::adjust(w.rect(), overlay_rect).contains(self.cursor.x, self.cursor.y))
+    }
+
+    /// Moves a window through the given function, remembering where it was so the move can be
+    /// undone.
+    fn move_window(
+        &mut self,
+        index: usize,
+        move_fn: impl FnOnce(&mut WindowInfo) -> Result<(), Box<dyn Error>>
+    ) {
+        let window = &mut self.windows[index];
+
+        self.history.push((window.id(), window.rect()));
+
+        // The window may have been closed since the last refresh, which is caught up with right
+        // away instead of waiting for the next one.
+        if let Err(e) = move_fn(window) {
+            log::error!("Could not move the window \"{}\": {}", window.title(), e);
+
+            self.history.pop();
+            self.next_refresh = Instant::now();
+        }
+    }
+
+    /// Enumerates the windows again, so the ones opened, closed, moved or resized while the
+    /// overlay is open are reflected by it. Whatever refers to a window that's gone is dropped.
+    fn refresh_windows(&mut self, state: &mut State) {
+        self.next_refresh = Instant::now() + WINDOW_REFRESH_INTERVAL;
+
+        let windows = match get_windows() {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("Could not enumerate application windows: {}", e);
+                return;
+            }
+        };
+
+        let exists = |id: isize| windows.iter().any(|w| w.id() == id);
+
+        let opened = windows.iter().filter(|w| !self.windows.iter().any(|o| o.id() == w.id())).count();
+        let closed = self.windows.iter().filter(|w| !exists(w.id())).count();
+
+        if opened > 0 || closed > 0 {
+            log::debug!("{} windows opened and {} closed since the last refresh.", opened, closed);
+        }
+
+        self.selected.retain(|id| exists(*id));
+        self.nudged.retain(|id| exists(*id));
+
+        if self.drag.as_ref().is_some_and(|d| !exists(d.window)) {
+            self.drag = None;
+        }
+
+        if state.scene.menu.as_ref().is_some_and(|m| !exists(m.window)) {
+            state.scene.menu = None;
+        }
+
+        self.windows = windows;
+        self.centered = Self::centered_rects();
+        self.sync(state);
+    }
+
+    /// Returns the rects of the windows that are already centered. They're only needed for the
+    /// outlines, so failing to enumerate them leaves them out.
+    fn centered_rects() -> Vec<Rect> {
+        match get_centered_windows() {
+            Ok(windows) => windows.iter().map(WindowInfo::rect).collect(),
+            Err(e) => {
+                log::warn!("Could not enumerate centered windows: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Moves the selected windows, or the hovered one if nothing is selected, by the given amount
+    /// of pixels.
+    fn nudge(&mut self, state: &State, x: i32, y: i32) {
+        self.nudged = match self.selected.is_empty() {
+            true => self.hovered_window(state).map(|i| self.windows[i].id()).into_iter().collect(),
+            false => self.selected.clone(),
+        };
+
+        for id in self.nudged.clone() {
+            if let Some(index) = self.windows.iter().position(|w| w.id() == id) {
+                self.move_window(index, |w| w.move_by(x, y));
+            }
+        }
+    }
+
+    /// Moves a window back to where it was before its last move. If no id is given, the last move
+    /// of any window is undone.
+    ///
+    /// Undoing a move isn't a move in itself, so it's not added to the history.
+    fn undo(&mut self, id: Option<isize>) {
+        let Some(position) = self.history
+            .iter()
+            .rposition(|(i, _)| id.is_none_or(|id| *i == id)) else {
+            return;
+        };
+
+        let (id, rect) = self.history.remove(position);
+
+        if let Some(window) = self.windows.iter_mut().find(|w| w.id() == id) {
+            if let Err(e) = window.set_rect(rect) {
+                log::error!("Could not move the window \"{}\" back: {}", window.title(), e);
+                self.next_refresh = Instant::now();
+            }
+        }
+    }
+
+    /// Lists the actions the menu offers for the given window.
+    fn menu_actions(&self, window: &WindowInfo) -> Vec<MenuAction> {
+        let mut actions = vec![
+            MenuAction::Center,
+            MenuAction::CenterHorizontally,
+            MenuAction::CenterVertically,
+            MenuAction::FitAndCenter,
+        ];
+
+        if self.monitors.len() > 1 {
+            actions.extend((0..self.monitors.len()).map(MenuAction::MoveToMonitor));
+        }
+
+        if self.history.iter().any(|(id, _)| *id == window.id()) {
+            actions.push(MenuAction::Undo);
+        }
+
+        if let Some(app) = window.app() {
+            actions.push(MenuAction::IgnoreApp(app.to_string()));
+        }
+
+        actions
+    }
+
+    /// Performs a menu action on the window with the given id.
+    fn perform(&mut self, action: MenuAction, id: isize) {
+        let Some(index) = self.windows.iter().position(|w| w.id() == id) else {
+            return;
+        };
+
+        match action {
+            MenuAction::Center => self.move_window(index, WindowInfo::center),
+            MenuAction::CenterHorizontally => self.move_window(index, WindowInfo::center_horizontally),
+            MenuAction::CenterVertically => self.move_window(index, WindowInfo::center_vertically),
+            MenuAction::FitAndCenter => self.move_window(index, WindowInfo::fit_and_center),
+
+            MenuAction::MoveToMonitor(monitor) => {
+                let monitor = self.monitors[monitor].clone();
+
+                self.move_window(index, |w| w.center_on(monitor));
+            }
+
+            MenuAction::Undo => self.undo(Some(id)),
+
+            MenuAction::IgnoreApp(app) => {
+                if let Err(e) = ignore_app(&app) {
+                    log::error!("Could not ignore \"{}\": {}", app, e);
+                }
+
+                self.windows.retain(|w| w.app() != Some(&app));
+                self.selected.retain(|s| self.windows.iter().any(|w| w.id() == *s));
+            }
+        }
+    }
+
+    /// Assigns a hint to every window and hands the ones matching the typed letters to the state,
+    /// so they can be drawn over their windows.
+    fn update_hints(state: &mut State, windows: &[WindowInfo], input: &str) {
+        if !Preferences::get().show_hints {
+            return;
+        }
+
+        let overlay_rect = state.rect();
+
+        state.scene.hints = windows
+            .iter()
+            .zip(hint_labels(windows.len()))
+            .filter(|(_, label)| label.starts_with(input))
+            .map(|(w, label)| (Rect::adjust(w.rect(), overlay_rect), label))
+            .collect();
+    }
+
+    /// Hands the windows matching the search query to the state, alongside the best match, so
+    /// they can be highlighted. Clears them if there's no query.
+    fn update_search(state: &mut State, windows: &[WindowInfo], query: Option<&str>) {
+        state.scene.search = query.map(String::from);
+        state.scene.matches.clear();
+        state.scene.best_match = None;
+
+        let Some(query) = query else {
+            return;
+        };
+
+        let overlay_rect = state.rect();
+
+        state.scene.matches = windows
+            .iter()
+            .filter(|w| w.search_score(query).is_some())
+            .map(|w| Rect::adjust(w.rect(), overlay_rect))
+            .collect();
+
+        state.scene.best_match = Self::best_match(windows, query)
+            .map(|index| {
+                let window = &windows[index];
+                let title = window.title().chars().take(SEARCH_TITLE_LENGTH).collect();
+
+                (Rect::adjust(window.rect(), overlay_rect), title)
+            });
+    }
+
+    /// Finds the index of the window that best matches the query.
+    /// Windows are listed from top to bottom, so on a tie the topmost window wins.
+    fn best_match(windows: &[WindowInfo], query: &str) -> Option<usize> {
+        windows
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(index, w)| w.search_score(query).map(|score| (index, score)))
+            .max_by_key(|(_, score)| *score)
+            .map(|(index, _)| index)
+    }
+
+    /**
+    This function calculates the display area for the overlay window to be rendered on.
+
+    It will return the position and size of the overlay window.
+    */
+    pub fn calculate_display_area(
+        event_loop: &ActiveEventLoop
+    ) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        // The overlay spans the union of every monitor, whose top-left corner may be anywhere on
+        // the desktop, including at negative coordinates or away from the origin.
+        let area = event_loop
+            .available_monitors()
+            .map(|m| Rect::new(m.position().x, m.position().y, m.size().width, m.size().height))
+            .reduce(Rect::union)
+            .unwrap_or_default();
+
+        (
+            PhysicalPosition::new(area.left, area.top),
+            PhysicalSize::new(area.width(), area.height())
+        )
+    }
+
+    /// Returns the position, size and scale factor of every monitor, in the order they're listed.
+    fn monitor_layout(event_loop: &ActiveEventLoop) -> Vec<MonitorLayout> {
+        event_loop
+            .available_monitors()
+            .map(|m| MonitorLayout {
+                position: m.position(),
+                size: m.size(),
+                scale_factor: m.scale_factor(),
+            })
+            .collect()
+    }
+
+    /// Creates the overlay's windows, one for each monitor, and the state drawn on them. Hidden
+    /// windows are shown once the overlay is opened.
+    fn create_state(event_loop: &ActiveEventLoop, visible: bool) -> Option<State<'a>> {
+        let (position, size) = Self::calculate_display_area(event_loop);
+
+        // Every monitor gets its own window, rather than one spanning all of them, so each is
+        // drawn at its own scale factor and nothing is drawn over the gaps between them.
+        let overlay_windows = event_loop
+            .available_monitors()
+            .enumerate()
+            .filter_map(|(index, monitor)| {
+                let window = Self::create_window(event_loop, &monitor, visible && index == 0, visible);
+
+                if let Err(e) = &window {
+                    log::error!("Could not create the overlay window of {:?}: {}", monitor.name(), e);
+                }
+
+                window.ok()
+            })
+            .collect::<Vec<_>>();
+
+        let overlay_rect = Rect::new(position.x, position.y, size.width, size.height);
+
+        let state = match State::new(overlay_windows, overlay_rect) {
+            Ok(s) => s,
+            Err(e) => {
+                log::error!("Could not create a renderer for the overlay: {}", e);
+                return None;
+            }
+        };
+
+        if state.viewports.is_empty() {
+            log::error!("No overlay window could be created.");
+            return None;
+        }
+
+        Some(state)
+    }
+
+    /// Creates the overlay's windows again after the monitors changed, alongside the monitors and
+    /// the windows they hold. The overlay stays open as it was, except for whatever was placed
+    /// relative to the old layout (the lasso and the menu).
+    fn rebuild(&mut self, event_loop: &ActiveEventLoop, state: State<'a>) -> Option<State<'a>> {
+        log::info!("The monitors changed, creating the overlay windows again.");
+
+        // The old windows (and their surfaces) go away before the new ones are created.
+        let animation = {
+            let State { animation, .. } = state;
+            animation
+        };
+
+        let mut state = Self::create_state(event_loop, true)?;
+
+        state.animation = animation;
+
+        match get_monitors() {
+            Ok(monitors) => self.monitors = monitors,
+            Err(e) => log::warn!("Could not enumerate monitors: {}", e),
+        }
+
+        self.layout = Self::monitor_layout(event_loop);
+        self.lasso_origin = None;
+        self.drag = None;
+
+        state.request_redraw();
+
+        Some(state)
+    }
+
+    /// Creates an overlay window covering the given monitor.
+    fn create_window(
+        event_loop: &ActiveEventLoop,
+        monitor: &MonitorHandle,
+        active: bool,
+        visible: bool,
+    ) -> Result<Window, Box<dyn Error>> {
+        let position = monitor.position();
+        let size = monitor.size();
+
+        let window_attributes = Window::default_attributes()
+            .with_active(active)
+            .with_content_protected(true)
+            .with_decorations(false)
+            .with_enabled_buttons(WindowButtons::empty())
+            .with_inner_size(size)
+            .with_position(position)
+            .with_resizable(false)
+            .with_title("Centered Windows")
+            .with_transparent(true)
+            .with_visible(visible)
+            .with_window_level(WindowLevel::AlwaysOnTop);
+
+        // Specific window settings on Windows OS, including the application's icon if possible.
+        #[cfg(target_os = "windows")]
+        let icon = match Icon::from_path("icon.ico", None) {
+            Ok(i) => Some(i),
+            Err(e) => {
+                log::warn!("Could not load icon: {}", e);
+                None
+            }
+        };
+
+        #[cfg(target_os = "windows")]
+        let window_attributes = window_attributes
+            .with_corner_preference(CornerPreference::DoNotRound)
+            .with_drag_and_drop(false)
+            .with_taskbar_icon(icon);
+
+        let window = event_loop.create_window(window_attributes)?;
+
+        log::debug!(
+            "Overlay window with position ({}, {}) and size [{}x{}] created.",
+            position.x,
+            position.y,
+            size.width,
+            size.height
+        );
+
+        Ok(window)
+    }
+}
+
+
+/**
+The values shared by every pixel of the overlay's dim layer. Matches `Globals` in the shader.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct Globals {
+    pub(crate) dim: [f32; 4],
+    pub(crate) count: u32,
+    pub(crate) glow: f32,
+    pub(crate) opacity: f32,
+    _padding: u32,
+}
+
+
+/**
+A rounded rect cut out of the overlay's dim layer, in overlay coordinates. Matches `Cutout` in the
+shader.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct Cutout {
+    pub(crate) rect: [f32; 4],
+    pub(crate) color: [f32; 4],
+    pub(crate) radius: f32,
+    /// The width of the highlight border. Zero means the cutout isn't highlighted.
+    pub(crate) border: f32,
+    _padding: [f32; 2],
+}
+
+impl Cutout {
+    fn new(rect: Rect, color: Color, radius: f32, border: f32) -> Self {
+        Self {
+            rect: [rect.left as f32, rect.top as f32, rect.right as f32, rect.bottom as f32],
+            color: color.0,
+            radius,
+            border,
+            _padding: [0.0; 2],
+        }
+    }
+
+    /// Moves the cutout by the given offset.
+    fn offset(mut self, x: f32, y: f32) -> Self {
+        self.rect[0] += x;
+        self.rect[1] += y;
+        self.rect[2] += x;
+        self.rect[3] += y;
+        self
+    }
+}
+
+
+/// The letters hints are made of, starting with the home row so they're quick to type.
+const HINT_ALPHABET: &str = "asdfghjkl";
+
+/// Generates `count` unique hints. Every hint has the same length so that none of them is the
+/// prefix of another, which means a window is matched as soon as its hint is fully typed.
+fn hint_labels(count: usize) -> Vec<String> {
+    let alphabet = HINT_ALPHABET.chars().collect::<Vec<_>>();
+    let mut length = 1;
+
+    while alphabet.len().pow(length) < count {
+        length += 1;
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = String::with_capacity(length as usize);
+
+            for _ in 0..length {
+                label.insert(0, alphabet[index % alphabet.len()]);
+                index /= alphabet.len();
+            }
+
+            label
+        })
+        .collect()
+}
+
+/// Checks whether the typed text is made only of hint letters.
+fn is_hint_input(text: &str) -> bool {
+    text.chars().all(|c| HINT_ALPHABET.contains(c.to_ascii_lowercase()))
+}
+
+/// The space left between a hint's letters and the edge of its background, in pixels.
+const HINT_PADDING: i32 = 4;
+
+/// How opaque the destination preview's fill is, relative to the theme's hovered color.
+const PREVIEW_FILL_OPACITY: f32 = 0.2;
+
+/// The thickness, in pixels, of the destination preview's outline.
+const PREVIEW_THICKNESS: i32 = 2;
+
+/// The distance from the top of the overlay to the center of the search bar, in pixels.
+const SEARCH_BAR_OFFSET: i32 = 64;
+
+/// The amount of characters of the best match's title shown over it.
+const SEARCH_TITLE_LENGTH: usize = 48;
+
+
+/// A window that has been pressed on. It becomes a drag once the cursor moves far enough.
+struct Drag {
+    /// The id of the pressed window.
+    window: isize,
+    /// Where the cursor was when the window was pressed.
+    origin: PhysicalPosition<i32>,
+    dragging: bool,
+}
+
+/// The distance, in pixels, the cursor has to move while pressing a window to start dragging it.
+const DRAG_THRESHOLD: i32 = 4;
+
+/// How often the windows are enumerated again while the overlay is open.
+const WINDOW_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Where a monitor is on the desktop and how it's scaled, which the overlay's windows follow.
+#[derive(Debug, Clone, PartialEq)]
+struct MonitorLayout {
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+}
+
+/// The outline of a monitor, alongside the label that identifies it.
+#[derive(Debug, Clone, PartialEq)]
+struct MonitorOutline {
+    bounds: Rect,
+    work_area: Rect,
+    label: String,
+}
+
+/// The colors of the outlines of the full and work areas of monitors.
+const OUTLINE_MONITOR: [f32; 4] = [0.37, 0.67, 1.0, 0.9];
+const OUTLINE_WORK_AREA: [f32; 4] = [0.37, 0.67, 1.0, 0.4];
+
+/// The thickness, in pixels, of the outlines of windows and monitors.
+const OUTLINE_THICKNESS: i32 = 1;
+const OUTLINE_MONITOR_THICKNESS: i32 = 3;
+
+/// The color of the guides while the hovered window doesn't line up with them.
+const GUIDE_IDLE: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
+
+/// The thickness, in pixels, of the guides.
+const GUIDE_THICKNESS: i32 = 2;
+
+/// The distance between the cursor and the information panel, in pixels.
+const INFO_CURSOR_OFFSET: i32 = 20;
+
+/// The amount of characters of the hovered window's title shown in the information panel.
+const INFO_TITLE_LENGTH: usize = 64;
+
+/// The amount of pixels a window is nudged by with the arrow keys, alone, with shift and with
+/// control.
+const NUDGE_SMALL: i32 = 1;
+const NUDGE_MEDIUM: i32 = 10;
+const NUDGE_LARGE: i32 = 50;
+
+/// The distance from the bottom of a nudged window to the center of its offset label, in pixels.
+const NUDGE_LABEL_OFFSET: i32 = 32;
+
+/// The colors of a dragged window's proxy and of the monitor it would be dropped on.
+const DRAG_PROXY: [f32; 4] = [0.37, 0.67, 1.0, 0.35];
+const DRAG_TARGET: [f32; 4] = [0.37, 0.67, 1.0, 0.9];
+
+/// The thickness, in pixels, of the outline around the monitor a window would be dropped on.
+const DRAG_THICKNESS: i32 = 4;
+
+/// An action that can be performed on a window through the context menu.
+#[derive(Debug, Clone, PartialEq)]
+enum MenuAction {
+    Center,
+    CenterHorizontally,
+    CenterVertically,
+    FitAndCenter,
+    /// Centers the window on the monitor with the given index.
+    MoveToMonitor(usize),
+    /// Moves the window back to where it was before its last move.
+    Undo,
+    /// Hides every window of the given application from the overlay.
+    IgnoreApp(String),
+}
+
+impl fmt::Display for MenuAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Center => write!(f, "Center"),
+            Self::CenterHorizontally => write!(f, "Center horizontally"),
+            Self::CenterVertically => write!(f, "Center vertically"),
+            Self::FitAndCenter => write!(f, "Fit and center"),
+            Self::MoveToMonitor(index) => write!(f, "Move to monitor {}", index + 1),
+            Self::Undo => write!(f, "Undo last move"),
+            Self::IgnoreApp(app) => write!(f, "Never show {app} again"),
+        }
+    }
+}
+
+/// A context menu listing the actions available for a window, drawn by the overlay.
+#[derive(Debug, Clone, PartialEq)]
+struct Menu {
+    /// The id of the window the actions are performed on.
+    window: isize,
+    rect: Rect,
+    /// Every action alongside the rect of its row, in overlay coordinates.
+    items: Vec<(MenuAction, Rect)>,
+    /// The index of the item under the cursor.
+    hovered: Option<usize>,
+}
+
+/// The color of the menu's highlighted item.
+const MENU_HIGHLIGHT: [f32; 4] = [0.3, 0.3, 0.3, 1.0];
+
+/// The space left around the text of every menu item, in pixels.
+const MENU_PADDING: i32 = 6;
+
+
+/// The thickness, in pixels, of the lasso selection outline.
+const LASSO_THICKNESS: i32 = 2;
+
+/// The format of the frames rendered offscreen.
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// The alpha modes that let the desktop show through the overlay, from most to least preferred.
+/// `Inherit` leaves it to the platform, which usually expects premultiplied colors.
+const TRANSPARENT_ALPHA_MODES: [wgpu::CompositeAlphaMode; 3] = [
+    wgpu::CompositeAlphaMode::PreMultiplied,
+    wgpu::CompositeAlphaMode::PostMultiplied,
+    wgpu::CompositeAlphaMode::Inherit,
+];
+
+/// How many frames in a row may fail to render, because the surface or the device was lost, before
+/// the GPU is given up on.
+const MAX_FAILED_FRAMES: u32 = 5;
+
+/// The size, in bytes, every buffer written to on each frame starts with.
+const INITIAL_BUFFER_SIZE: wgpu::BufferAddress = 4096;
+
+
+/**
+Everything that is drawn on top of the overlay's dim layer, in overlay coordinates.
+
+A frame is only rendered when the scene changes.
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Scene {
+    clip: Option<Rect>,
+    /// Where the hovered window would end up if it were centered.
+    preview: Option<Rect>,
+    selection: Vec<Rect>,
+    lasso: Option<Rect>,
+    hints: Vec<(Rect, String)>,
+    search: Option<String>,
+    matches: Vec<Rect>,
+    best_match: Option<(Rect, String)>,
+    /// The nudged windows alongside their offset from the center.
+    offsets: Vec<(Rect, String)>,
+    /// The outline of every window, and whether the window is already centered.
+    outlines: Vec<(Rect, bool)>,
+    monitor_outlines: Vec<MonitorOutline>,
+    /// The guides through the center of every monitor, and whether they're lit.
+    guides: Vec<(Rect, bool)>,
+    /// The position and lines of the information panel of the hovered window.
+    info: Option<(PhysicalPosition<i32>, Vec<String>)>,
+    menu: Option<Menu>,
+    /// The proxy of a dragged window and the monitor it would be dropped on.
+    drag: Option<(Rect, Option<Rect>)>,
+}
+
+impl Scene {
+    /// Collects every sprite drawn on top of the dim layer, in the order they're drawn.
+    /// Only the part of the scene inside of the given viewport, in overlay coordinates, is visible.
+    fn sprites<'b>(&self, theme: &Theme, atlas: &'b GlyphAtlas, viewport: Rect, opacity: f32) -> SpriteBatch<'b> {
+        let mut batch = SpriteBatch::new(atlas, viewport);
+
+        batch.set_opacity(opacity);
+
+        for outline in &self.monitor_outlines {
+            batch.push_outline(outline.bounds, OUTLINE_MONITOR_THICKNESS, OUTLINE_MONITOR);
+            batch.push_outline(outline.work_area, OUTLINE_THICKNESS, OUTLINE_WORK_AREA);
+
+            let position = PhysicalPosition::new(
+                outline.bounds.left + OUTLINE_MONITOR_THICKNESS,
+                outline.bounds.top + OUTLINE_MONITOR_THICKNESS,
+            );
+
+            batch.push_panel(position, std::slice::from_ref(&outline.label), MENU_PADDING, theme.panel_color.0, theme.text_color.0);
+        }
+
+        for (rect, centered) in &self.outlines {
+            let color = if *centered { theme.centered_color.0 } else { theme.candidate_color.0 };
+
+            batch.push_outline(*rect, OUTLINE_THICKNESS, color);
+        }
+
+        for (rect, lit) in &self.guides {
+            batch.push_rect(*rect, if *lit { theme.centered_color.0 } else { GUIDE_IDLE });
+        }
+
+        if let Some((proxy, target)) = self.drag {
+            if let Some(target) = target {
+                batch.push_outline(target, DRAG_THICKNESS, DRAG_TARGET);
+            }
+
+            batch.push_rect(proxy, DRAG_PROXY);
+            batch.push_outline(proxy, PREVIEW_THICKNESS, DRAG_TARGET);
+        }
+
+        if let Some(preview) = self.preview {
+            batch.push_rect(preview, theme.hovered_color.fade(PREVIEW_FILL_OPACITY).0);
+            batch.push_outline(preview, PREVIEW_THICKNESS, theme.hovered_color.0);
+        }
+
+        for (rect, label) in &self.hints {
+            let center = PhysicalPosition::new(
+                rect.left + (rect.width() / 2) as i32,
+                rect.top + (rect.height() / 2) as i32,
+            );
+
+            batch.push_label(center, label, HINT_PADDING, theme.label_background.0, theme.label_color.0);
+        }
+
+        if let Some(query) = &self.search {
+            // Every monitor shows the search bar, so it's in sight wherever the user is looking.
+            let center = PhysicalPosition::new(
+                viewport.left + (viewport.width() / 2) as i32,
+                viewport.top + SEARCH_BAR_OFFSET,
+            );
+            let text = format!("Search: {query}");
+
+            batch.push_label(center, &text, HINT_PADDING * 2, theme.panel_color.0, theme.text_color.0);
+        }
+
+        if let Some((rect, title)) = &self.best_match {
+            let center = PhysicalPosition::new(
+                rect.left + (rect.width() / 2) as i32,
+                rect.top + (rect.height() / 2) as i32,
+            );
+
+            batch.push_label(center, title, HINT_PADDING, theme.label_background.0, theme.label_color.0);
+        }
+
+        for (rect, offset) in &self.offsets {
+            let center = PhysicalPosition::new(
+                rect.left + (rect.width() / 2) as i32,
+                rect.bottom - NUDGE_LABEL_OFFSET,
+            );
+
+            batch.push_label(center, offset, HINT_PADDING, theme.panel_color.0, theme.text_color.0);
+        }
+
+        if let Some((position, lines)) = &self.info {
+            batch.push_panel(*position, lines, MENU_PADDING, theme.panel_color.0, theme.text_color.0);
+        }
+
+        if let Some(menu) = &self.menu {
+            batch.push_rect(menu.rect, theme.panel_color.0);
+
+            for (index, (action, rect)) in menu.items.iter().enumerate() {
+                if menu.hovered == Some(index) {
+                    batch.push_rect(*rect, MENU_HIGHLIGHT);
+                }
+
+                let position = PhysicalPosition::new(rect.left + MENU_PADDING, rect.top + MENU_PADDING);
+
+                batch.push_text(position, &action.to_string(), theme.text_color.0);
+            }
+        }
+
+        batch
+    }
+}
+
+
+
+/**
+The time-based transitions of the overlay: fading in and out as it opens and closes, and the
+highlighted cutout moving between hovered windows.
+
+While any of them is running, a new frame is requested as soon as the last one is rendered.
+ */
+#[derive(Debug)]
+struct Animation {
+    fade_duration: Duration,
+    hover_duration: Duration,
+    opened_at: Instant,
+    closing_at: Option<Instant>,
+    /// Where the highlighted cutout was when it started moving towards the hovered window.
+    hover_from: Option<(Rect, Instant)>,
+}
+
+impl Animation {
+    /// Creates the animation state of an overlay that's being opened right now. Every duration
+    /// is zero if the user prefers reduced motion.
+    fn new() -> Self {
+        let preferences = Preferences::get();
+        let reduced = prefers_reduced_motion();
+
+        let duration = |ms| if reduced { Duration::ZERO } else { Duration::from_millis(ms) };
+
+        if reduced {
+            log::debug!("Reduced motion preferred, overlay animations are disabled.");
+        }
+
+        Self {
+            fade_duration: duration(preferences.fade_duration),
+            hover_duration: duration(preferences.hover_duration),
+            opened_at: Instant::now(),
+            closing_at: None,
+            hover_from: None,
+        }
+    }
+
+    /// Creates the animation state of an overlay that's shown right away, without transitions.
+    fn still() -> Self {
+        Self {
+            fade_duration: Duration::ZERO,
+            hover_duration: Duration::ZERO,
+            opened_at: Instant::now(),
+            closing_at: None,
+            hover_from: None,
+        }
+    }
+
+    /// Returns how far along a transition that started at the given time is, from 0 to 1.
+    fn progress(start: Instant, duration: Duration) -> f32 {
+        if duration.is_zero() {
+            return 1.0;
+        }
+
+        (start.elapsed().as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Eases a progress value out, so transitions start fast and settle down smoothly.
+    fn ease(t: f32) -> f32 {
+        1.0 - (1.0 - t).powi(3)
+    }
+
+    /// The opacity of the whole overlay, from 0 to 1.
+    fn opacity(&self) -> f32 {
+        let opened = Self::ease(Self::progress(self.opened_at, self.fade_duration));
+
+        match self.closing_at {
+            Some(start) => opened * (1.0 - Self::ease(Self::progress(start, self.fade_duration))),
+            None => opened,
+        }
+    }
+
+    /// Returns where the highlighted cutout is drawn, on its way to the given target.
+    fn clip(&self, target: Option<Rect>) -> Option<Rect> {
+        let Some((from, start)) = self.hover_from else {
+            return target;
+        };
+
+        let target = target?;
+        let t = Self::ease(Self::progress(start, self.hover_duration));
+
+        let lerp = |a: i32, b: i32| a + ((b - a) as f32 * t).round() as i32;
+
+        Some(Rect {
+            left: lerp(from.left, target.left),
+            top: lerp(from.top, target.top),
+            right: lerp(from.right, target.right),
+            bottom: lerp(from.bottom, target.bottom),
+        })
+    }
+
+    /// Starts moving the highlighted cutout towards a new target, from wherever it's drawn now.
+    /// The cutout only moves between windows, it appears and disappears without a transition.
+    fn move_clip(&mut self, current: Option<Rect>, target: Option<Rect>) {
+        if current == target {
+            return;
+        }
+
+        self.hover_from = match (current, target) {
+            (Some(_), Some(_)) => self.clip(current).map(|from| (from, Instant::now())),
+            _ => None,
+        };
+    }
+
+    fn close(&mut self) {
+        if self.closing_at.is_none() {
+            self.closing_at = Some(Instant::now());
+        }
+    }
+
+    fn is_closing(&self) -> bool {
+        self.closing_at.is_some()
+    }
+
+    /// Whether the overlay has completely faded out after being closed.
+    fn is_closed(&self) -> bool {
+        self.closing_at
+            .is_some_and(|start| Self::progress(start, self.fade_duration) >= 1.0)
+    }
+
+    /// Whether any transition is still in progress.
+    fn is_running(&self) -> bool {
+        let running = |start: Instant, duration: Duration| Self::progress(start, duration) < 1.0;
+
+        running(self.opened_at, self.fade_duration)
+            || self.closing_at.is_some_and(|s| running(s, self.fade_duration))
+            || self.hover_from.is_some_and(|(_, s)| running(s, self.hover_duration))
+    }
+}
+
+struct State<'a> {
+    size: PhysicalSize<u32>,
+    /// The position of the overlay's top-left corner on the desktop.
+    position: PhysicalPosition<i32>,
+
+    /// The windows the overlay is drawn on, one for each monitor.
+    viewports: Vec<Viewport<'a>>,
+    /// The device shared by every viewport drawn with the GPU, created along with the first one.
+    gpu: Option<Arc<GpuContext>>,
+
+    theme: Theme,
+    scene: Scene,
+    animation: Animation,
+}
+
+
+/**
+One of the windows the overlay is made of, covering a single monitor. Every viewport draws the part
+of the scene it covers, so gaps between monitors are left alone and every window keeps the scale
+factor of its own monitor.
+ */
+struct Viewport<'a> {
+    /// The area covered by the viewport, in overlay coordinates.
+    rect: Rect,
+    /// The scale factor of the viewport's monitor, which labels and cutouts are sized with.
+    scale_factor: f64,
+    /// The glyphs of the labels, rasterized at the viewport's scale factor.
+    atlas: GlyphAtlas,
+    renderer: Renderer<'a>,
+    /// How many frames in a row the renderer failed to draw.
+    failed_frames: u32,
+    window: Option<Arc<Window>>,
+}
+
+
+/// Draws the frames of the overlay, either with the GPU or on the CPU when no adapter works.
+enum Renderer<'a> {
+    Gpu(Box<Gpu<'a>>),
+    Software(SoftwareRenderer),
+}
+
+
+/**
+The adapter and device every viewport is drawn with, so monitors don't each get their own.
+ */
+struct GpuContext {
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    /// Set when the device is lost (driver reset, GPU switch...), which makes it unusable.
+    lost: Arc<AtomicBool>,
+}
+
+
+/**
+Everything needed to draw a viewport with wgpu.
+ */
+struct Gpu<'a> {
+    /// The surface of the viewport's window, `None` when frames are only rendered offscreen.
+    surface: Option<wgpu::Surface<'a>>,
+    /// Presents frames rendered offscreen to the window, when its surface can't be transparent.
+    presenter: Option<SoftwareRenderer>,
+    context: Arc<GpuContext>,
+    config: wgpu::SurfaceConfiguration,
+    render_pipeline: wgpu::RenderPipeline,
+    sprite_pipeline: wgpu::RenderPipeline,
+    atlas_layout: wgpu::BindGroupLayout,
+    atlas_bind_group: wgpu::BindGroup,
+
+    // The buffers are kept between frames and only recreated when they're too small.
+    globals_buffer: wgpu::Buffer,
+    cutout_buffer: wgpu::Buffer,
+    cutout_layout: wgpu::BindGroupLayout,
+    cutout_bind_group: wgpu::BindGroup,
+    sprite_vertex_buffer: wgpu::Buffer,
+    sprite_index_buffer: wgpu::Buffer,
+}
+
+
+impl<'a> State<'a> {
+    /// Creates the state of an overlay covering the given area of the desktop, drawn on the given
+    /// windows.
+    pub fn new(windows: Vec<Window>, rect: Rect) -> Result<Self, Box<dyn Error>> {
+        let theme = Theme::get().clone();
+        let mut gpu = None;
+
+        let viewports = windows
+            .into_iter()
+            .map(|window| {
+                let position = window.inner_position().unwrap_or_default();
+                let size = window.inner_size();
+                let area = Rect::new(position.x, position.y, size.width, size.height);
+                let scale_factor = window.scale_factor();
+
+                Viewport::new(
+                    Preferences::get().renderer,
+                    Some(Arc::new(window)),
+                    Rect::adjust(area, rect),
+                    scale_factor,
+                    &theme,
+                    &mut gpu,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            size: PhysicalSize::new(rect.width(), rect.height()),
+            position: PhysicalPosition::new(rect.left, rect.top),
+
+            viewports,
+            gpu,
+
+            theme,
+            scene: Scene::default(),
+            animation: Animation::new(),
+        })
+    }
+
+    /// Creates the state of an overlay covering the given area of the desktop, without a window.
+    /// Its frames can only be rendered offscreen, see [`State::render_offscreen`].
+    pub fn offscreen(
+        rect: Rect,
+        scale_factor: f64,
+        theme: Theme,
+        renderer: RendererKind,
+    ) -> Result<Self, Box<dyn Error>> {
+        let size = PhysicalSize::new(rect.width(), rect.height());
+        let mut gpu = None;
+
+        let viewport = Viewport::new(
+            renderer,
+            None,
+            Rect::new(0, 0, size.width, size.height),
+            scale_factor,
+            &theme,
+            &mut gpu,
+        )?;
+
+        Ok(Self {
+            size,
+            position: PhysicalPosition::new(rect.left, rect.top),
+
+            viewports: vec![viewport],
+            gpu,
+
+            theme,
+            scene: Scene::default(),
+            animation: Animation::still(),
+        })
+    }
+}
+
+
+impl<'a> Viewport<'a> {
+    /// Creates a viewport drawn at the given scale factor. GPU renderers use the given device,
+    /// which is created first if there's none yet.
+    fn new(
+        kind: RendererKind,
+        window: Option<Arc<Window>>,
+        rect: Rect,
+        scale_factor: f64,
+        theme: &Theme,
+        gpu: &mut Option<Arc<GpuContext>>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let size = PhysicalSize::new(rect.width(), rect.height());
+        let atlas = Self::create_atlas(theme, scale_factor);
+
+        Ok(Self {
+            rect,
+            scale_factor,
+            renderer: Self::create_renderer(kind, window.clone(), size, &atlas, gpu)?,
+            atlas,
+            failed_frames: 0,
+            window,
+        })
+    }
+
+    fn create_atlas(theme: &Theme, scale_factor: f64) -> GlyphAtlas {
+        let px = theme.font_size * scale_factor as f32;
+
+        match GlyphAtlas::build(&theme.font, px) {
+            Ok(a) => a,
+            Err(e) => {
+                log::warn!("Could not load the label font \"{}\", using the built-in one: {}", theme.font, e);
+                GlyphAtlas::built_in(px)
+            }
+        }
+    }
+
+    /// Creates the renderer picked in the preferences. When it's left to decide, the GPU is used
+    /// unless no adapter (or device) can be used, in which case the overlay is drawn on the CPU.
+    fn create_renderer(
+        kind: RendererKind,
+        window: Option<Arc<Window>>,
+        size: PhysicalSize<u32>,
+        atlas: &GlyphAtlas,
+        gpu: &mut Option<Arc<GpuContext>>,
+    ) -> Result<Renderer<'a>, Box<dyn Error>> {
+        let renderer = match kind {
+            RendererKind::Gpu => Renderer::Gpu(Box::new(Gpu::new(gpu, window, size, atlas)?)),
+            RendererKind::Software => Renderer::Software(SoftwareRenderer::new(window)?),
+            RendererKind::Auto => match Gpu::new(gpu, window.clone(), size, atlas) {
+                Ok(gpu) => Renderer::Gpu(Box::new(gpu)),
+                Err(e) => {
+                    log::warn!("Could not render with the GPU, falling back to software rendering: {}", e);
+                    Renderer::Software(SoftwareRenderer::new(window)?)
+                }
+            },
+        };
+
+        log::debug!("Renderer created and ready to use.");
+
+        Ok(renderer)
+    }
+}
+
+
+impl GpuContext {
+    /// Picks an adapter able to draw on the given window and creates a device with it. Without a
+    /// window, frames are only rendered offscreen and a software adapter is preferred, so that
+    /// they look the same on every machine.
+    fn new(window: Option<Arc<Window>>) -> Result<Self, Box<dyn Error>> {
+        let instance = Gpu::create_instance();
+
+        let (adapter, limits) = match window {
+            Some(window) => {
+                // The surface is only needed to find a compatible adapter, every viewport creates
+                // its own afterwards.
+                let surface = instance.create_surface(window)?;
+                let adapter = Gpu::create_adapter(&instance, Some(&surface), false)
+                    .ok_or("no compatible adapter was found")?;
+
+                log::info!("Rendering with adapter: {}", Gpu::describe_adapter(&adapter));
+
+                (adapter, wgpu::Limits::default())
+            }
+            None => {
+                let adapter = [true, false]
+                    .into_iter()
+                    .find_map(|force_fallback_adapter| Gpu::create_adapter(&instance, None, force_fallback_adapter))
+                    .ok_or("no adapter was found")?;
+
+                log::debug!("Rendering offscreen with adapter: {}", Gpu::describe_adapter(&adapter));
+
+                let limits = wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits());
+
+                (adapter, limits)
+            }
+        };
+
+        let (device, queue) = Gpu::create_device(&adapter, limits)?;
+        let lost = Arc::new(AtomicBool::new(false));
+
+        device.set_device_lost_callback({
+            let lost = lost.clone();
+
+            move |reason, message| {
+                log::error!("The GPU device was lost ({:?}): {}", reason, message);
+                lost.store(true, Ordering::Relaxed);
+            }
+        });
+
+        // Errors would panic otherwise. Running out of memory can't be recovered from without a
+        // new device.
+        device.on_uncaptured_error({
+            let lost = lost.clone();
+
+            Box::new(move |error| {
+                log::error!("GPU error: {}", error);
+
+                if let wgpu::Error::OutOfMemory { .. } = error {
+                    lost.store(true, Ordering::Relaxed);
+                }
+            })
+        });
+
+        Ok(Self { instance, adapter, device, queue, lost })
+    }
+
+    /// Returns the given device, or creates one in its place if there's none or it's unusable.
+    fn get_or_create(gpu: &mut Option<Arc<Self>>, window: Option<Arc<Window>>) -> Result<Arc<Self>, Box<dyn Error>> {
+        if let Some(context) = gpu.as_ref().filter(|c| !c.is_lost()) {
+            return Ok(context.clone());
+        }
+
+        let context = Arc::new(Self::new(window)?);
+
+        *gpu = Some(context.clone());
+
+        Ok(context)
+    }
+
+    fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+}
+
+
+impl<'a> Gpu<'a> {
+    /// Sets up rendering to the given window, or offscreen when there's no window, with the given
+    /// device. A new device is created first if there's none or it's unusable.
+    fn new(
+        gpu: &mut Option<Arc<GpuContext>>,
+        window: Option<Arc<Window>>,
+        size: PhysicalSize<u32>,
+        atlas: &GlyphAtlas,
+    ) -> Result<Self, Box<dyn Error>> {
+        let context = GpuContext::get_or_create(gpu, window.clone())?;
+
+        let Some(window) = window else {
+            return Ok(Self::with_context(context, Self::create_offscreen_config(size), atlas));
+        };
+
+        let surface = context.instance.create_surface(window.clone())?;
+
+        // The adapter was picked for another monitor, which may be plugged into another GPU.
+        if !context.adapter.is_surface_supported(&surface) {
+            return Err("the adapter can't draw on the window".into());
+        }
+
+        let surface_capabilities = surface.get_capabilities(&context.adapter);
+
+        log::info!(
+            "The surface supports the formats {:?}, the present modes {:?} and the alpha modes {:?}.",
+            surface_capabilities.formats,
+            surface_capabilities.present_modes,
+            surface_capabilities.alpha_modes,
+        );
+
+        let Some(alpha_mode) = Self::choose_alpha_mode(&surface_capabilities.alpha_modes) else {
+            // An opaque surface would cover the desktop, so frames are rendered offscreen and
+            // handed to the window instead, which blends them with the desktop by itself.
+            log::warn!(
+                "The surface only supports the {:?} alpha modes, presenting frames through the window instead.",
+                surface_capabilities.alpha_modes
+            );
+
+            drop(surface);
+
+            let mut gpu = Self::with_context(context, Self::create_offscreen_config(size), atlas);
+
+            gpu.presenter = Some(SoftwareRenderer::new(Some(window))?);
+
+            return Ok(gpu);
+        };
+
+        log::info!("Presenting with the {:?} alpha mode.", alpha_mode);
+
+        let config = Self::create_surface_config(size, surface_capabilities, alpha_mode);
+
+        surface.configure(&context.device, &config);
+
+        let mut gpu = Self::with_context(context, config, atlas);
+
+        gpu.surface = Some(surface);
+
+        Ok(gpu)
+    }
+
+    /// There's no surface to configure when rendering offscreen, only the size and format of the
+    /// frames are used. Colors are kept straight.
+    fn create_offscreen_config(size: PhysicalSize<u32>) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: OFFSCREEN_FORMAT,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::PostMultiplied,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        }
+    }
+
+    /// Creates the pipelines and buffers needed to render with the given device.
+    fn with_context(context: Arc<GpuContext>, config: wgpu::SurfaceConfiguration, atlas: &GlyphAtlas) -> Self {
+        let device = &context.device;
+        let queue = &context.queue;
+
+        let cutout_layout = Self::create_cutout_bind_group_layout(device);
+        let render_pipeline = Self::create_render_pipeline(device, &config, &cutout_layout);
+
+        let atlas_layout = Self::create_atlas_bind_group_layout(device);
+        let sprite_pipeline = Self::create_sprite_pipeline(device, &config, &atlas_layout);
+        let atlas_bind_group = Self::create_atlas_bind_group(device, queue, &atlas_layout, atlas);
+        
+        let globals_buffer = Self::create_buffer(device, "Globals Buffer", wgpu::BufferUsages::UNIFORM, size_of::<Globals>() as _);
+        let cutout_buffer = Self::create_buffer(device, "Cutout Buffer", wgpu::BufferUsages::STORAGE, INITIAL_BUFFER_SIZE);
+        let cutout_bind_group = Self::create_cutout_bind_group(device, &cutout_layout, &globals_buffer, &cutout_buffer);
+        let sprite_vertex_buffer = Self::create_buffer(device, "Sprite Vertex Buffer", wgpu::BufferUsages::VERTEX, INITIAL_BUFFER_SIZE);
+        let sprite_index_buffer = Self::create_buffer(device, "Sprite Index Buffer", wgpu::BufferUsages::INDEX, INITIAL_BUFFER_SIZE);
+
+        Self {
+            surface: None,
+            presenter: None,
+            context,
+            config,
+            render_pipeline,
+            sprite_pipeline,
+            atlas_layout,
+            atlas_bind_group,
+            globals_buffer,
+            cutout_buffer,
+            cutout_layout,
+            cutout_bind_group,
+            sprite_vertex_buffer,
+            sprite_index_buffer,
+        }
+    }
+
+    fn create_instance() -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: Self::backends(Preferences::get().backend),
+            ..Default::default()
+        })
+    }
+
+    fn backends(backend: GraphicsBackend) -> wgpu::Backends {
+        match backend {
+            GraphicsBackend::Auto => wgpu::Backends::all(),
+            GraphicsBackend::Vulkan => wgpu::Backends::VULKAN,
+            GraphicsBackend::Gl => wgpu::Backends::GL,
+            GraphicsBackend::Dx12 => wgpu::Backends::DX12,
+        }
+    }
+
+    /// Describes an adapter on a single line, to tell which one was picked from the logs.
+    fn describe_adapter(adapter: &wgpu::Adapter) -> String {
+        let info = adapter.get_info();
+
+        format!(
+            "{} ({:?}, {:?} backend, driver: {} {})",
+            info.name,
+            info.device_type,
+            info.backend,
+            info.driver,
+            info.driver_info,
+        )
+    }
+
+    fn create_adapter(
+        instance: &wgpu::Instance,
+        surface: Option<&wgpu::Surface>,
+        force_fallback_adapter: bool,
+    ) -> Option<wgpu::Adapter> {
+        instance
+            .request_adapter(
+                &wgpu::RequestAdapterOptions {
+                    power_preference: match Preferences::get().power_preference {
+                        PowerPreference::Default => wgpu::PowerPreference::default(),
+                        PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+                        PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+                    },
+                    compatible_surface: surface,
+                    force_fallback_adapter,
+                }
+            )
+            .block_on()
+    }
+
+    fn create_device(
+        adapter: &wgpu::Adapter,
+        limits: wgpu::Limits,
+    ) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: limits,
+                    memory_hints: wgpu::MemoryHints::default(),
+                    label: None,
+                },
+                None
+            )
+            .block_on()
+    }
+
+    fn create_surface_config(
+        size: PhysicalSize<u32>,
+        capabilities: wgpu::SurfaceCapabilities,
+        alpha_mode: wgpu::CompositeAlphaMode,
+    ) -> wgpu::SurfaceConfiguration {
+        // Looks for a sRGB compatible surface.
+        let surface_format = capabilities.formats
+            .iter()
+            .find(|f| f.is_srgb())
+            .copied()
+            .unwrap_or(capabilities.formats[0]);
+
+        let preferences = Preferences::get();
+
+        let present_mode = match preferences.present_mode {
+            PresentMode::AutoVsync => wgpu::PresentMode::AutoVsync,
+            PresentMode::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        };
+
+        // The automatic modes are always supported, as they fall back to one that is.
+        let present_mode = match present_mode {
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => present_mode,
+            _ if capabilities.present_modes.contains(&present_mode) => present_mode,
+            _ => {
+                log::warn!("The {:?} present mode isn't supported, using AutoVsync instead.", present_mode);
+                wgpu::PresentMode::AutoVsync
+            }
+        };
+
+        log::info!(
+            "Presenting {:?} frames with the {:?} present mode and a latency of {} frames.",
+            surface_format,
+            present_mode,
+            preferences.frame_latency,
+        );
+
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode,
+            alpha_mode,
+            view_formats: vec![],
+            desired_maximum_frame_latency: preferences.frame_latency,
+        }
+    }
+
+    /// Picks an alpha mode that lets the desktop show through the overlay, if the surface has one.
+    fn choose_alpha_mode(modes: &[wgpu::CompositeAlphaMode]) -> Option<wgpu::CompositeAlphaMode> {
+        TRANSPARENT_ALPHA_MODES
+            .into_iter()
+            .find(|m| modes.contains(m))
+    }
+
+    /// Whether the colors drawn to the surface must be premultiplied by their alpha, which the
+    /// compositor expects unless the surface uses straight alpha. Frames rendered offscreen are
+    /// kept straight.
+    fn is_premultiplied(config: &wgpu::SurfaceConfiguration) -> bool {
+        matches!(
+            config.alpha_mode,
+            wgpu::CompositeAlphaMode::PreMultiplied | wgpu::CompositeAlphaMode::Inherit
+        )
+    }
+
+    /// Returns the pipeline constants shared by both shaders.
+    fn shader_constants(config: &wgpu::SurfaceConfiguration) -> HashMap<String, f64> {
+        HashMap::from([("premultiplied".to_string(), Self::is_premultiplied(config) as u8 as f64)])
+    }
+
+    /// Returns how sprites are blended with what's beneath them.
+    fn sprite_blend_state(config: &wgpu::SurfaceConfiguration) -> wgpu::BlendState {
+        if Self::is_premultiplied(config) {
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING
+        } else {
+            wgpu::BlendState::ALPHA_BLENDING
+        }
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        cutout_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(include_str!("overlay.wgsl").into()),
+        });
+
+        let constants = Self::shader_constants(config);
+
+        let layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[cutout_layout],
+                push_constant_ranges: &[],
+            },
+        );
+
+        device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: Some("Render Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    // The vertices of the fullscreen triangle are generated by the shader.
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions {
+                        constants: &constants,
+                        ..Default::default()
+                    },
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format: config.format,
+                            blend: Some(wgpu::BlendState::REPLACE),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }),
+                    ],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            },
+        )
+    }
+
+    fn create_cutout_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Cutout Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_cutout_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        globals_buffer: &wgpu::Buffer,
+        cutout_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Cutout Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: globals_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: cutout_buffer.as_entire_binding(),
+                },
+            ],
+        })
     }
 
-    fn create_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
-        adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    memory_hints: wgpu::MemoryHints::default(),
-                    label: None,
+    fn create_atlas_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Atlas Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
                 },
-                None
-            )
-            .block_on()
-            .unwrap()
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
     }
 
-    fn create_surface_config(
-        size: PhysicalSize<u32>,
-        capabilities: wgpu::SurfaceCapabilities
-    ) -> wgpu::SurfaceConfiguration {
-        // Looks for a sRGB compatible surface.
-        let surface_format = capabilities.formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(capabilities.formats[0]);
+    fn create_atlas_bind_group(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        atlas: &GlyphAtlas,
+    ) -> wgpu::BindGroup {
+        let size = atlas.size();
 
-        wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::AutoNoVsync,
-            alpha_mode: capabilities.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        }
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Atlas Texture"),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            atlas.pixels(),
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Glyphs are always drawn at the size they were rasterized with, so there's no need to
+        // filter the texture.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Atlas Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        })
     }
 
-    fn create_render_pipeline(
+    fn create_sprite_pipeline(
         device: &wgpu::Device,
-        config: &wgpu::SurfaceConfiguration
+        config: &wgpu::SurfaceConfiguration,
+        atlas_layout: &wgpu::BindGroupLayout,
     ) -> wgpu::RenderPipeline {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(include_str!("overlay.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(include_str!("sprite.wgsl").into()),
         });
 
+        let constants = Self::shader_constants(config);
+
         let layout = device.create_pipeline_layout(
             &wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[],
+                label: Some("Sprite Pipeline Layout"),
+                bind_group_layouts: &[atlas_layout],
                 push_constant_ranges: &[],
             },
         );
 
+        // Unlike the clipping quads, sprites are blended on top of whatever is beneath them.
         device.create_render_pipeline(
             &wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
+                label: Some("Sprite Pipeline"),
                 layout: Some(&layout),
                 vertex: wgpu::VertexState {
                     module: &shader,
                     entry_point: Some("vs_main"),
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                     buffers: &[
-                        Vertex::desc(),
+                        SpriteVertex::desc(),
                     ],
                 },
                 fragment: Some(wgpu::FragmentState {
                     module: &shader,
                     entry_point: Some("fs_main"),
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    compilation_options: wgpu::PipelineCompilationOptions {
+                        constants: &constants,
+                        ..Default::default()
+                    },
                     targets: &[
                         Some(wgpu::ColorTargetState {
                             format: config.format,
-                            blend: Some(wgpu::BlendState::REPLACE),
+                            blend: Some(Self::sprite_blend_state(config)),
                             write_mask: wgpu::ColorWrites::ALL,
                         }),
                     ],
@@ -448,68 +2754,208 @@ impl<'a> State<'a> {
         )
     }
 
-    fn create_vertex_buffer(device: &wgpu::Device, vertices: &[Vertex]) -> wgpu::Buffer {
-        device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(vertices),
-                usage: wgpu::BufferUsages::VERTEX,
+    fn create_buffer(
+        device: &wgpu::Device,
+        label: &str,
+        usage: wgpu::BufferUsages,
+        size: wgpu::BufferAddress,
+    ) -> wgpu::Buffer {
+        device.create_buffer(
+            &wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: usage | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
             },
         )
     }
 
-    fn create_index_buffer(device: &wgpu::Device, indices: &[u16]) -> wgpu::Buffer {
-        device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(indices),
-                usage: wgpu::BufferUsages::INDEX,
-            },
-        )
+    /// Writes the given contents at the start of a buffer, replacing it with a bigger one if they
+    /// don't fit.
+    fn write_buffer<T: bytemuck::Pod>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        buffer: &mut wgpu::Buffer,
+        contents: &[T],
+    ) -> wgpu::BufferAddress {
+        let bytes = bytemuck::cast_slice::<T, u8>(contents);
+
+        // Writes must be aligned, which might not be the case for an odd amount of indices.
+        let size = (bytes.len() as wgpu::BufferAddress).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+
+        if size > buffer.size() {
+            let usage = buffer.usage() - wgpu::BufferUsages::COPY_DST;
+
+            log::debug!("Growing a buffer from {} to {} bytes.", buffer.size(), size.next_power_of_two());
+
+            *buffer = Self::create_buffer(device, "Resized Buffer", usage, size.next_power_of_two());
+        }
+
+        let mut data = bytes.to_vec();
+        data.resize(size as usize, 0);
+
+        queue.write_buffer(buffer, 0, &data);
+
+        bytes.len() as wgpu::BufferAddress
     }
 }
 
 
-impl<'a> State<'a> {
-    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
-        self.size = new_size;
+impl<'a> Gpu<'a> {
+    /// Draws the labels with the glyphs of the given atlas from now on.
+    fn set_atlas(&mut self, atlas: &GlyphAtlas) {
+        let context = &self.context;
+
+        self.atlas_bind_group = Self::create_atlas_bind_group(&context.device, &context.queue, &self.atlas_layout, atlas);
+    }
 
+    fn resize(&mut self, new_size: PhysicalSize<u32>) {
         self.config.width = new_size.width;
         self.config.height = new_size.height;
 
-        self.surface.configure(&self.device, &self.config);
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.context.device, &self.config);
+        }
     }
 
-    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+    /// Renders a frame to the overlay's window, if it has one.
+    fn render(&mut self, globals: &Globals, cutouts: &[Cutout], batch: &SpriteBatch) -> Result<(), wgpu::SurfaceError> {
+        if let Some(mut presenter) = self.presenter.take() {
+            let result = self.render_offscreen(globals, cutouts, batch)
+                .and_then(|frame| presenter.present(&frame));
+
+            if let Err(e) = result {
+                log::error!("Could not present a frame: {}", e);
+            }
+
+            self.presenter = Some(presenter);
+
+            return Ok(());
+        }
+
+        let Some(surface) = &self.surface else {
+            return Ok(());
+        };
+
+        let output = surface.get_current_texture()?;
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        self.draw(&view, globals, cutouts, batch);
+
+        output.present();
+
+        Ok(())
+    }
+
+    /// Renders a frame into a texture and reads it back.
+    fn render_offscreen(&mut self, globals: &Globals, cutouts: &[Cutout], batch: &SpriteBatch) -> Result<Frame, Box<dyn Error>> {
+        let size = PhysicalSize::new(self.config.width, self.config.height);
+
+        let extent = wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = self.context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Texture"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        self.draw(&texture.create_view(&wgpu::TextureViewDescriptor::default()), globals, cutouts, batch);
+
+        // Every row copied into a buffer has to be aligned, so rows are padded and then trimmed.
+        let row_size = size.width * 4;
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = self.context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Buffer"),
+            size: (padded_row_size * size.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Encoder"),
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            extent,
+        );
+
+        self.context.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        self.context.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let pixels = slice
+            .get_mapped_range()
+            .chunks(padded_row_size as usize)
+            .flat_map(|row| &row[..row_size as usize])
+            .copied()
+            .collect();
+
+        buffer.unmap();
+
+        Ok(Frame::new(size, pixels))
+    }
+
+    /// Records and submits every pass needed to draw a frame into the given texture.
+    fn draw(&mut self, view: &wgpu::TextureView, globals: &Globals, cutouts: &[Cutout], batch: &SpriteBatch) {
+        let mut encoder = self.context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
 
-        {
-            let preferences = Preferences::get();
-            
-            let r = preferences.overlay_color >> 16;
-            let g = (preferences.overlay_color >> 8) & 0xFF;
-            let b = preferences.overlay_color & 0xFF;
+        self.context.queue.write_buffer(&self.globals_buffer, 0, bytemuck::bytes_of(globals));
+
+        // Storage buffers can't be empty, so there's always at least one (unused) cutout.
+        let cutouts = if cutouts.is_empty() { &[bytemuck::Zeroable::zeroed()] } else { cutouts };
+        let cutout_buffer_size = self.cutout_buffer.size();
+
+        Self::write_buffer(&self.context.device, &self.context.queue, &mut self.cutout_buffer, cutouts);
 
+        if self.cutout_buffer.size() != cutout_buffer_size {
+            self.cutout_bind_group = Self::create_cutout_bind_group(
+                &self.context.device,
+                &self.cutout_layout,
+                &self.globals_buffer,
+                &self.cutout_buffer,
+            );
+        }
+
+        {
             let mut render_pass = encoder.begin_render_pass(
                 &wgpu::RenderPassDescriptor {
                     label: Some("Render Pass"),
                     color_attachments: &[
                         Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
+                            view,
                             resolve_target: None,
                             ops: wgpu::Operations {
-                                // Default clear color is Black with 60% opacity.
-                                load: wgpu::LoadOp::Clear(wgpu::Color {
-                                    r: r as f64 / 255.0,
-                                    g: g as f64 / 255.0,
-                                    b: b as f64 / 255.0,
-                                    a: preferences.overlay_opacity,
-                                }),
+                                // The dim layer is drawn by the shader, covering everything.
+                                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                                 store: wgpu::StoreOp::Store,
                             },
                         },
@@ -519,62 +2965,429 @@ impl<'a> State<'a> {
                     timestamp_writes: None,
                 },
             );
-            
-            // If there is a "clip" rect, this will call the shader code and provide the same clipping
-            // area to "cut" a quad in the overlay.
-            // Otherwise, the overlay will be fully rendered with the provided color attachment.
-            if let Some(clip) = self.clip {
-                // The initial point (0, 0) of the render area in WebGPU is the middle of the screen area.
-                // By halving the width and height of the overlay, the initial point is acquired.
-                let center_x = self.size.width as f32 / 2.0;
-                let center_y = self.size.height as f32 / 2.0;
-
-                // A transformation is applied to the clipping rect to remap it to the overlay's
-                // coordinates. The top (1) and bottom (3) are multiplied by -1 to invert the coordinates. 
-                let rect = clip.raw();
-                let (left, top, right, bottom) = (
-                    rect.0 as f32 - center_x,
-                    (rect.1 as f32 - center_y) * -1.0,
-                    rect.2 as f32 - center_x,
-                    (rect.3 as f32 - center_y) * -1.0,
-                );
-                
-                let vertices = &[
-                    Vertex { position: [right / center_x, top / center_y] },
-                    Vertex { position: [left / center_x, top / center_y] },
-                    Vertex { position: [left / center_x, bottom / center_y] },
-                    Vertex { position: [right / center_x, bottom / center_y] },
-                ];
 
-                let vertex_buffer = Self::create_vertex_buffer(&self.device, vertices);
-                let index_buffer = Self::create_index_buffer(&self.device, INDICES);
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(0, &self.cutout_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+
+            // Sprites (the destination preview and labels such as hints) are drawn on top of
+            // everything else.
+            if !batch.is_empty() {
+                let vertices_size = Self::write_buffer(&self.context.device, &self.context.queue, &mut self.sprite_vertex_buffer, batch.vertices());
+                let indices_size = Self::write_buffer(&self.context.device, &self.context.queue, &mut self.sprite_index_buffer, batch.indices());
+
+                render_pass.set_pipeline(&self.sprite_pipeline);
+                render_pass.set_bind_group(0, &self.atlas_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, self.sprite_vertex_buffer.slice(..vertices_size));
+                render_pass.set_index_buffer(self.sprite_index_buffer.slice(..indices_size), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..batch.indices().len() as _, 0, 0..1);
+            }
+        }
+
+        self.context.queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+
+impl<'a> State<'a> {
+    /// Renders a frame of the given viewport, recovering from GPU failures: the surface is
+    /// reconfigured when it's lost or outdated, and everything is created again when the device
+    /// is lost or out of memory. Failed frames are retried, until too many fail in a row and the
+    /// CPU takes over. Only failures that hint at a broken GPU count towards that, since outdated
+    /// surfaces and timeouts happen on their own when windows are resized or monitors sleep.
+    ///
+    /// Returns whether the frame was drawn.
+    fn redraw(&mut self, index: usize) -> bool {
+        let (globals, cutouts) = self.dim_layer(&self.viewports[index]);
+        let opacity = self.animation.opacity();
+
+        let viewport = &mut self.viewports[index];
+        let viewport_rect = viewport.rect;
+
+        // A lost device is handled like one out of memory, neither can be used anymore.
+        let result = match viewport.is_lost() {
+            true => Err(wgpu::SurfaceError::OutOfMemory),
+            false => viewport.render(&globals, &cutouts, &self.scene, &self.theme, opacity),
+        };
+
+        let failed = match result {
+            Ok(()) => {
+                viewport.failed_frames = 0;
+                return true;
+            }
+
+            Err(wgpu::SurfaceError::Outdated) => {
+                log::debug!("The surface is outdated, configuring it again.");
+                viewport.resize(PhysicalSize::new(viewport_rect.width(), viewport_rect.height()));
+                false
+            }
+
+            Err(wgpu::SurfaceError::Timeout) => {
+                log::warn!("Timed out waiting for the next surface texture.");
+                false
+            }
+
+            Err(wgpu::SurfaceError::Lost) => {
+                log::warn!("The surface is lost, configuring it again.");
+                viewport.resize(PhysicalSize::new(viewport_rect.width(), viewport_rect.height()));
+                true
+            }
 
-                render_pass.set_pipeline(&self.render_pipeline);
-                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                render_pass.draw_indexed(0..INDICES.len() as _, 0, 0..1);
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::warn!("The GPU device is unusable, creating a new one.");
+                self.recreate_gpu();
+                true
             }
+        };
+
+        let viewport = &mut self.viewports[index];
+
+        if failed {
+            viewport.failed_frames += 1;
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if viewport.failed_frames >= MAX_FAILED_FRAMES {
+            log::error!("{} frames in a row failed, falling back to software rendering.", viewport.failed_frames);
 
-        Ok(())
+            match SoftwareRenderer::new(viewport.window.clone()) {
+                Ok(renderer) => viewport.renderer = Renderer::Software(renderer),
+                Err(e) => log::error!("Could not create the software renderer: {}", e),
+            }
+
+            viewport.failed_frames = 0;
+        }
+
+        viewport.request_redraw();
+
+        false
+    }
+
+    /// Replaces the device every viewport is drawn with, which can't be used anymore, creating
+    /// their GPU renderers again with a new one.
+    fn recreate_gpu(&mut self) {
+        // Every window can only have one surface at a time, so the old ones go first.
+        for viewport in &mut self.viewports {
+            if let Renderer::Gpu(gpu) = &mut viewport.renderer {
+                gpu.surface = None;
+                gpu.presenter = None;
+            }
+        }
+
+        self.gpu = None;
+
+        for viewport in &mut self.viewports {
+            viewport.recreate_gpu(&mut self.gpu);
+        }
+    }
+
+    /// Resizes the given viewport, keeping its top-left corner.
+    pub fn resize(&mut self, index: usize, new_size: PhysicalSize<u32>) {
+        self.viewports[index].resize(new_size);
+    }
+
+    /// Draws the given viewport at a new scale factor, once its monitor was rescaled.
+    pub fn set_scale_factor(&mut self, index: usize, scale_factor: f64) {
+        self.viewports[index].set_scale_factor(scale_factor, &self.theme);
+    }
+
+    /// Renders a frame of the whole overlay into memory instead of its windows. Offscreen states
+    /// only have one viewport, covering all of it.
+    pub fn render_offscreen(&mut self) -> Result<Frame, Box<dyn Error>> {
+        let (globals, cutouts) = self.dim_layer(&self.viewports[0]);
+        let opacity = self.animation.opacity();
+
+        self.viewports[0].render_offscreen(&globals, &cutouts, &self.scene, &self.theme, opacity)
+    }
+
+    /// Returns what the dim layer of the given viewport is drawn with: its globals and every
+    /// cutout, moved into the viewport's coordinates and sized for its scale factor.
+    fn dim_layer(&self, viewport: &Viewport) -> (Globals, Vec<Cutout>) {
+        let preferences = Preferences::get();
+
+        let r = preferences.overlay_color >> 16;
+        let g = (preferences.overlay_color >> 8) & 0xFF;
+        let b = preferences.overlay_color & 0xFF;
+
+        // Every cutout (the hovered window, the selected windows and the lasso outline) is handed
+        // to the shader, which dims everything outside of them in a single fullscreen pass.
+        let cutouts = self.cutouts(viewport.scale_factor)
+            .into_iter()
+            .map(|c| c.offset(-viewport.rect.left as f32, -viewport.rect.top as f32))
+            .collect::<Vec<_>>();
+
+        let globals = Globals {
+            // Default dim color is Black with 60% opacity.
+            dim: [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, preferences.overlay_opacity as f32],
+            count: cutouts.len() as u32,
+            glow: self.theme.glow as f32 * viewport.scale_factor as f32,
+            opacity: self.animation.opacity(),
+            _padding: 0,
+        };
+
+        (globals, cutouts)
     }
     
+    /// Closes the overlay, once it fades out.
+    fn close(&mut self) {
+        self.animation.close();
+        self.request_redraw();
+    }
+
+    /// Sets the window under the cursor, which gets cut out of the overlay. A preview of where the
+    /// window would be centered is shown alongside it.
+    pub fn hover(&mut self, window: Option<&WindowInfo>) {
+        let overlay_rect = self.rect();
+        let clip = window.map(|w| Rect::adjust(w.rect(), overlay_rect));
+
+        self.animation.move_clip(self.scene.clip, clip);
+        self.scene.clip = clip;
+        self.scene.preview = window
+            .filter(|_| Preferences::get().show_preview)
+            .map(|w| Rect::adjust(w.centered_rect(), overlay_rect));
+    }
+
+    /// Opens a menu with the given actions for a window, with its top-left corner at the given
+    /// position. The menu is kept inside of the overlay.
+    fn open_menu(&mut self, position: PhysicalPosition<i32>, window: isize, actions: Vec<MenuAction>) {
+        let Some(viewport) = self.viewport_at(position.x, position.y) else {
+            return;
+        };
+
+        // Without a font the menu couldn't be read, so it's not opened at all.
+        let atlas = &viewport.atlas;
+
+        if !atlas.has_font() {
+            return;
+        }
+
+        let labels = actions.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let width = labels
+            .iter()
+            .map(|l| atlas.measure(l).width as i32)
+            .max()
+            .unwrap_or(0) + MENU_PADDING * 2;
+        let item_height = atlas.measure("").height as i32 + MENU_PADDING * 2;
+        let height = item_height * actions.len() as i32;
+
+        // The menu is kept inside of the monitor it's opened on, so it's never split between two.
+        let bounds = viewport.rect;
+
+        let left = position.x.min(bounds.right - width).max(bounds.left);
+        let top = position.y.min(bounds.bottom - height).max(bounds.top);
+
+        let items = actions
+            .into_iter()
+            .enumerate()
+            .map(|(index, action)| {
+                let rect = Rect::new(left, top + item_height * index as i32, width as u32, item_height as u32);
+
+                (action, rect)
+            })
+            .collect();
+
+        self.scene.menu = Some(Menu {
+            window,
+            rect: Rect::new(left, top, width as u32, height as u32),
+            items,
+            hovered: None,
+        });
+    }
+
+    /// Collects every rect that should be cut out of the overlay, with corners and borders sized
+    /// for the given scale factor.
+    fn cutouts(&self, scale_factor: f64) -> Vec<Cutout> {
+        let theme = &self.theme;
+        let scale = scale_factor as f32;
+        let radius = theme.corner_radius as f32 * scale;
+        let border = theme.border_width as f32 * scale;
+
+        // While searching, the matching windows are highlighted and the rest remain dimmed.
+        let mut cutouts = self.scene.selection
+            .iter()
+            .chain(&self.scene.matches)
+            .map(|rect| Cutout::new(*rect, theme.selected_color, radius, border))
+            .collect::<Vec<_>>();
+
+        if let Some(clip) = self.animation.clip(self.scene.clip) {
+            cutouts.push(Cutout::new(clip, theme.border_color, radius, border));
+        }
+
+        // The lasso is drawn as an outline made of four thin strips.
+        if let Some(lasso) = self.scene.lasso {
+            let (left, top, right, bottom) = lasso.raw();
+
+            cutouts.extend([
+                Rect { left, top, right, bottom: top + LASSO_THICKNESS },
+                Rect { left, top: bottom - LASSO_THICKNESS, right, bottom },
+                Rect { left, top, right: left + LASSO_THICKNESS, bottom },
+                Rect { left: right - LASSO_THICKNESS, top, right, bottom },
+            ].map(|rect| Cutout::new(rect, theme.selected_color, 0.0, 0.0)));
+        }
+
+        cutouts
+    }
+
     pub fn rect(&self) -> Rect {
-        let position = self.window.inner_position().unwrap();
-        let size = self.size;
-        
         Rect::new(
-            position.x,
-            position.y,
-            size.width,
-            size.height,
+            self.position.x,
+            self.position.y,
+            self.size.width,
+            self.size.height,
         )
     }
 
-    pub fn window(&self) -> &Window {
-        &self.window
+    /// Finds the viewport drawn on the given window.
+    fn viewport_index(&self, id: WindowId) -> Option<usize> {
+        self.viewports
+            .iter()
+            .position(|v| v.window.as_ref().is_some_and(|w| w.id() == id))
+    }
+
+    /// Returns the viewport containing the given point, in overlay coordinates.
+    fn viewport_at(&self, x: i32, y: i32) -> Option<&Viewport<'a>> {
+        self.viewports
+            .iter()
+            .find(|v| v.rect.contains(x, y))
+    }
+
+    /// Whether any of the overlay's windows has the keyboard focus.
+    fn has_focus(&self) -> bool {
+        self.viewports
+            .iter()
+            .any(|v| v.window.as_ref().is_some_and(|w| w.has_focus()))
+    }
+
+    /// Shows every window of the overlay, focusing the first one so keys reach the overlay.
+    fn show(&self) {
+        for window in self.viewports.iter().filter_map(|v| v.window.as_ref()) {
+            window.set_visible(true);
+        }
+
+        if let Some(window) = self.viewports.first().and_then(|v| v.window.as_ref()) {
+            window.focus_window();
+        }
+    }
+
+    fn hide(&self) {
+        for window in self.viewports.iter().filter_map(|v| v.window.as_ref()) {
+            window.set_visible(false);
+        }
+    }
+
+    fn set_cursor(&self, cursor: CursorIcon) {
+        for window in self.viewports.iter().filter_map(|v| v.window.as_ref()) {
+            window.set_cursor(cursor);
+        }
+    }
+
+    fn request_redraw(&self) {
+        for viewport in &self.viewports {
+            viewport.request_redraw();
+        }
+    }
+}
+
+
+impl<'a> Viewport<'a> {
+    fn is_lost(&self) -> bool {
+        match &self.renderer {
+            Renderer::Gpu(gpu) => gpu.context.is_lost(),
+            Renderer::Software(_) => false,
+        }
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        self.rect = Rect::new(self.rect.left, self.rect.top, new_size.width, new_size.height);
+
+        if let Renderer::Gpu(gpu) = &mut self.renderer {
+            gpu.resize(new_size);
+        }
+    }
+
+    /// Renders a frame to the viewport's window, if it has one.
+    fn render(
+        &mut self,
+        globals: &Globals,
+        cutouts: &[Cutout],
+        scene: &Scene,
+        theme: &Theme,
+        opacity: f32,
+    ) -> Result<(), wgpu::SurfaceError> {
+        let batch = scene.sprites(theme, &self.atlas, self.rect, opacity);
+
+        match &mut self.renderer {
+            Renderer::Gpu(gpu) => gpu.render(globals, cutouts, &batch),
+            Renderer::Software(renderer) => {
+                let size = PhysicalSize::new(self.rect.width(), self.rect.height());
+                let frame = software::rasterize(size, globals, cutouts, &batch, &self.atlas);
+
+                if let Err(e) = renderer.present(&frame) {
+                    log::error!("Could not present a frame: {}", e);
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn render_offscreen(
+        &mut self,
+        globals: &Globals,
+        cutouts: &[Cutout],
+        scene: &Scene,
+        theme: &Theme,
+        opacity: f32,
+    ) -> Result<Frame, Box<dyn Error>> {
+        let batch = scene.sprites(theme, &self.atlas, self.rect, opacity);
+
+        match &mut self.renderer {
+            Renderer::Gpu(gpu) => gpu.render_offscreen(globals, cutouts, &batch),
+            Renderer::Software(_) => {
+                let size = PhysicalSize::new(self.rect.width(), self.rect.height());
+
+                Ok(software::rasterize(size, globals, cutouts, &batch, &self.atlas))
+            }
+        }
+    }
+
+    /// Replaces the GPU renderer with a new one drawn with the given device, once its surface is
+    /// gone. Viewports drawn on the CPU are left alone.
+    fn recreate_gpu(&mut self, gpu: &mut Option<Arc<GpuContext>>) {
+        let Renderer::Gpu(old) = &self.renderer else {
+            return;
+        };
+
+        let size = PhysicalSize::new(self.rect.width(), self.rect.height());
+
+        match Gpu::new(gpu, self.window.clone(), size, &self.atlas) {
+            Ok(new) => self.renderer = Renderer::Gpu(Box::new(new)),
+            Err(e) => {
+                log::error!("Could not create a new GPU renderer: {}", e);
+
+                // The old renderer has no surface left, so it's tried again on the next frame.
+                old.context.lost.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Rasterizes the glyphs again at the given scale factor, which labels and cutouts are sized
+    /// with from now on.
+    fn set_scale_factor(&mut self, scale_factor: f64, theme: &Theme) {
+        if self.scale_factor == scale_factor {
+            return;
+        }
+
+        self.scale_factor = scale_factor;
+        self.atlas = Self::create_atlas(theme, scale_factor);
+
+        if let Renderer::Gpu(gpu) = &mut self.renderer {
+            gpu.set_atlas(&self.atlas);
+        }
+    }
+
+    fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
     }
 }
@@ -1,19 +1,18 @@
 // Vertex Shader.
 
-struct VertexInput {
-	@location(0) position: vec2<f32>,
-}
-
 struct VertexOutput {
 	@builtin(position) position: vec4<f32>,
 }
 
 
+// A single triangle covering the whole overlay, so the fragment shader runs once for every pixel.
 @vertex
-fn vs_main(model: VertexInput) -> VertexOutput {
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
 	var out: VertexOutput;
 
-	out.position = vec4<f32>(model.position, 0.0, 1.0);
+	let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+	out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
 
 	return out;
 }
@@ -21,7 +20,106 @@ fn vs_main(model: VertexInput) -> VertexOutput {
 
 // Fragment Shader.
 
+struct Globals {
+	// The color of the dim layer.
+	dim: vec4<f32>,
+	count: u32,
+	// The distance, in pixels, the glow fades out over. Zero disables it.
+	glow: f32,
+	// The opacity of the whole overlay, while it fades in or out.
+	opacity: f32,
+}
+
+struct Cutout {
+	// The left, top, right and bottom edges, in pixels.
+	rect: vec4<f32>,
+	// The color of the border and glow.
+	color: vec4<f32>,
+	radius: f32,
+	// The width of the highlight border, in pixels. Zero disables the border and glow.
+	border: f32,
+}
+
+// Whether the surface expects colors premultiplied by their alpha. Set when creating the pipeline.
+override premultiplied: bool = false;
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+@group(0) @binding(1)
+var<storage, read> cutouts: array<Cutout>;
+
+
+// The signed distance from a point to a rounded rectangle: negative inside, positive outside.
+fn rounded_rect(point: vec2<f32>, rect: vec4<f32>, radius: f32) -> f32 {
+	let center = (rect.xy + rect.zw) * 0.5;
+	let half_size = (rect.zw - rect.xy) * 0.5;
+	let r = min(radius, min(half_size.x, half_size.y));
+	let q = abs(point - center) - half_size + r;
+
+	return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+// Composites a straight alpha color on top of another.
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+	let alpha = top.a + bottom.a * (1.0 - top.a);
+
+	if alpha <= 0.0 {
+		return vec4<f32>(0.0);
+	}
+
+	let color = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / alpha;
+
+	return vec4<f32>(color, alpha);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-	return vec4<f32>();
+	let point = in.position.xy;
+
+	var distance = 1e9;
+	var border = vec4<f32>(0.0);
+	var glow = vec4<f32>(0.0);
+
+	for (var i = 0u; i < globals.count; i++) {
+		let cutout = cutouts[i];
+		let d = rounded_rect(point, cutout.rect, cutout.radius);
+
+		distance = min(distance, d);
+
+		if cutout.border > 0.0 {
+			// The border is drawn outwards, so it never covers the window itself.
+			let ring = abs(d - cutout.border * 0.5) - cutout.border * 0.5;
+
+			let coverage = clamp(0.5 - ring, 0.0, 1.0) * cutout.color.a;
+
+			if coverage > border.a {
+				border = vec4<f32>(cutout.color.rgb, coverage);
+			}
+
+			if globals.glow > 0.0 && d > cutout.border {
+				let strength = exp(-3.0 * (d - cutout.border) / globals.glow) * cutout.color.a * 0.5;
+
+				if strength > glow.a {
+					glow = vec4<f32>(cutout.color.rgb, strength);
+				}
+			}
+		}
+	}
+
+	// Pixels are blended across the edge of the cutouts, which anti-aliases them.
+	let hole = clamp(0.5 - distance, 0.0, 1.0);
+
+	var color = vec4<f32>(globals.dim.rgb, globals.dim.a * (1.0 - hole));
+
+	color = over(vec4<f32>(glow.rgb, glow.a * (1.0 - hole)), color);
+	color = over(border, color);
+
+	let alpha = color.a * globals.opacity;
+
+	if premultiplied {
+		return vec4<f32>(color.rgb * alpha, alpha);
+	}
+
+	return vec4<f32>(color.rgb, alpha);
 }
@@ -0,0 +1,225 @@
+use std::error::Error;
+use std::fs;
+use std::io::Cursor;
+
+use serde::Deserialize;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::HWND;
+
+use super::{GenericError, MonitorInfo, Preferences, Rect, WindowInfo};
+
+
+/**
+A made up set of monitors and windows, used to render the overlay without looking at the real
+desktop. Desktops are written as YAML files:
+
+```yaml
+monitors:
+  - name: DISPLAY1
+    bounds: { left: 0, top: 0, right: 1920, bottom: 1080 }
+    work_area: { left: 0, top: 0, right: 1920, bottom: 1040 }
+windows:
+  - title: Untitled - Notepad
+    app: notepad
+    rect: { left: 100, top: 100, right: 900, bottom: 700 }
+```
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct Desktop {
+    /// The scale factor the overlay is drawn with, which affects the size of labels and borders.
+    #[serde(default = "Desktop::default_scale_factor")]
+    pub scale_factor: f64,
+    /// Whether windows are centered on the full area of the monitors instead of their work area.
+    /// Defaults to the [`Preferences::use_absolute_area`] preference.
+    pub use_absolute_area: Option<bool>,
+    pub monitors: Vec<DesktopMonitor>,
+    #[serde(default)]
+    pub windows: Vec<DesktopWindow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesktopMonitor {
+    pub name: String,
+    pub bounds: Rect,
+    /// Defaults to the full area of the monitor.
+    pub work_area: Option<Rect>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesktopWindow {
+    pub title: String,
+    pub app: Option<String>,
+    #[serde(default)]
+    pub class: String,
+    pub rect: Rect,
+}
+
+impl Desktop {
+    fn default_scale_factor() -> f64 {
+        1.0
+    }
+
+    /// Reads a desktop from the given YAML file.
+    pub fn try_from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let file = fs::File::open(path)?;
+        let desktop: Desktop = serde_yaml::from_reader(file)?;
+
+        if desktop.monitors.is_empty() {
+            return Err(GenericError::InvalidData.into());
+        }
+
+        Ok(desktop)
+    }
+
+    /// Returns the smallest rect containing every monitor, which is the area the overlay covers.
+    pub fn bounds(&self) -> Rect {
+        self.monitors
+            .iter()
+            .map(|m| m.bounds)
+            .reduce(Rect::union)
+            .unwrap_or_default()
+    }
+
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.monitors
+            .iter()
+            .map(|m| m.to_monitor_info(self.use_absolute_area()))
+            .collect()
+    }
+
+    /// Returns the windows of the desktop, in the same order. Every window is identified by its
+    /// position in the list, starting at 1.
+    pub fn windows(&self) -> Vec<WindowInfo> {
+        self.windows
+            .iter()
+            .enumerate()
+            .map(|(index, window)| window.to_window_info(index as isize + 1, self))
+            .collect()
+    }
+
+    fn use_absolute_area(&self) -> bool {
+        self.use_absolute_area.unwrap_or(Preferences::get().use_absolute_area)
+    }
+}
+
+impl DesktopMonitor {
+    fn to_monitor_info(&self, use_absolute_area: bool) -> MonitorInfo {
+        let work_area = self.work_area.unwrap_or(self.bounds);
+
+        let rect = if use_absolute_area {
+            self.bounds
+        } else {
+            work_area
+        };
+
+        MonitorInfo {
+            name: self.name.clone(),
+            position: PhysicalPosition::new(rect.left, rect.top),
+            size: PhysicalSize::new(rect.width(), rect.height()),
+            bounds: self.bounds,
+            work_area,
+        }
+    }
+}
+
+impl DesktopWindow {
+    /// Builds the window, placing it on the monitor that contains its center (or the first one).
+    fn to_window_info(&self, id: isize, desktop: &Desktop) -> WindowInfo {
+        let center_x = self.rect.left + (self.rect.width() / 2) as i32;
+        let center_y = self.rect.top + (self.rect.height() / 2) as i32;
+
+        let monitor = desktop.monitors
+            .iter()
+            .find(|m| m.bounds.contains(center_x, center_y))
+            .unwrap_or(&desktop.monitors[0]);
+
+        WindowInfo {
+            title: self.title.clone(),
+            app: self.app.clone(),
+            class: self.class.clone(),
+            position: PhysicalPosition::new(self.rect.left, self.rect.top),
+            size: PhysicalSize::new(self.rect.width(), self.rect.height()),
+            monitor: monitor.to_monitor_info(desktop.use_absolute_area()),
+            id,
+            // Desktop windows don't exist, so moving them fails on Windows. They can only be moved
+            // on a dry run.
+            #[cfg(target_os = "windows")]
+            handle: HWND::default(),
+        }
+    }
+}
+
+
+/**
+A frame of the overlay rendered offscreen, as tightly packed RGBA pixels with straight alpha.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    size: PhysicalSize<u32>,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(size: PhysicalSize<u32>, pixels: Vec<u8>) -> Self {
+        Self { size, pixels }
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the color of the pixel at the given coordinates.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * self.size.width + x) * 4) as usize;
+
+        [
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+            self.pixels[index + 3],
+        ]
+    }
+
+    /// Decodes a frame from an 8-bit RGBA PNG image.
+    pub fn from_png(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = png::Decoder::new(Cursor::new(data)).read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels)?;
+
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            return Err(GenericError::InvalidData.into());
+        }
+
+        pixels.truncate(info.buffer_size());
+
+        Ok(Self::new(PhysicalSize::new(info.width, info.height), pixels))
+    }
+
+    /// Encodes the frame as a PNG image.
+    pub fn to_png(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut data = Vec::new();
+
+        {
+            let mut encoder = png::Encoder::new(&mut data, self.size.width, self.size.height);
+
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            encoder.write_header()?.write_image_data(&self.pixels)?;
+        }
+
+        Ok(data)
+    }
+
+    pub fn save_png(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_png()?)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,486 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use winit::dpi::PhysicalSize;
+use winit::window::Window;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::{
+    Foundation::{COLORREF, HWND, SIZE},
+    Graphics::Gdi::{
+        AC_SRC_ALPHA,
+        AC_SRC_OVER,
+        BI_RGB,
+        BITMAPINFO,
+        BITMAPINFOHEADER,
+        BLENDFUNCTION,
+        CreateCompatibleDC,
+        CreateDIBSection,
+        DeleteDC,
+        DeleteObject,
+        DIB_RGB_COLORS,
+        GetDC,
+        ReleaseDC,
+        SelectObject,
+    },
+    UI::WindowsAndMessaging::{
+        GetWindowLongPtrW,
+        GWL_EXSTYLE,
+        SetWindowLongPtrW,
+        ULW_ALPHA,
+        UpdateLayeredWindow,
+        WS_EX_LAYERED,
+    },
+};
+
+#[cfg(target_os = "windows")]
+use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+use super::{Cutout, Frame, Globals, GlyphAtlas, SpriteBatch};
+
+
+/**
+Draws the overlay on the CPU, for machines where no GPU adapter can be used (virtual machines,
+remote desktop sessions or missing drivers).
+
+Frames are rasterized the same way the shaders do it, blending in linear space and storing sRGB
+colors, so they look just like the ones drawn by the GPU. They're then copied into a software
+buffer owned by the window, which relies on the platform's per-window alpha:
+
+- On Windows, the overlay becomes a layered window.
+- Elsewhere, frames are presented through `softbuffer` with premultiplied alpha, which shows the
+  desktop through the overlay when the window has an ARGB visual, and black otherwise.
+
+Frames rendered by the GPU are presented the same way when its surface can't be transparent.
+ */
+pub struct SoftwareRenderer {
+    #[cfg(not(target_os = "windows"))]
+    surface: Option<softbuffer::Surface<Arc<Window>, Arc<Window>>>,
+    #[cfg(target_os = "windows")]
+    window: Option<Arc<Window>>,
+}
+
+impl SoftwareRenderer {
+    /// Creates a renderer presenting to the given window, or one that only renders offscreen.
+    pub fn new(window: Option<Arc<Window>>) -> Result<Self, Box<dyn Error>> {
+        #[cfg(target_os = "windows")]
+        {
+            if let Some(window) = &window {
+                let hwnd = Self::hwnd(window)?;
+
+                unsafe {
+                    let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+                    SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+                }
+            }
+
+            Ok(Self { window })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let surface = match window {
+                Some(window) => {
+                    let context = softbuffer::Context::new(window.clone())?;
+                    Some(softbuffer::Surface::new(&context, window)?)
+                }
+                None => None,
+            };
+
+            Ok(Self { surface })
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn hwnd(window: &Window) -> Result<HWND, Box<dyn Error>> {
+        match window.window_handle()?.as_raw() {
+            RawWindowHandle::Win32(handle) => Ok(HWND(handle.hwnd.get() as _)),
+            _ => Err("the overlay is not a Win32 window".into()),
+        }
+    }
+
+    /// Shows a frame on the window, if the renderer has one.
+    pub fn present(&mut self, frame: &Frame) -> Result<(), Box<dyn Error>> {
+        #[cfg(target_os = "windows")]
+        if let Some(window) = &self.window {
+            Self::update_layered_window(Self::hwnd(window)?, frame)?;
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        if let Some(surface) = &mut self.surface {
+            let size = frame.size();
+
+            let (Some(width), Some(height)) = (size.width.try_into().ok(), size.height.try_into().ok()) else {
+                return Ok(());
+            };
+
+            surface.resize(width, height)?;
+
+            let mut buffer = surface.buffer_mut()?;
+
+            // Premultiplied colors are the same as blending with black, for when the alpha (the
+            // top byte) is ignored.
+            for (target, pixel) in buffer.iter_mut().zip(frame.pixels().chunks(4)) {
+                let alpha = pixel[3] as u32;
+                let [r, g, b] = [0, 1, 2].map(|i| (pixel[i] as u32 * alpha + 127) / 255);
+
+                *target = (alpha << 24) | (r << 16) | (g << 8) | b;
+            }
+
+            buffer.present()?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies the frame into a bitmap and hands it to the layered window, which blends it with the
+    /// desktop using its alpha.
+    #[cfg(target_os = "windows")]
+    fn update_layered_window(hwnd: HWND, frame: &Frame) -> Result<(), Box<dyn Error>> {
+        let size = frame.size();
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: size.width as i32,
+                // A negative height means the rows go from top to bottom, like in the frame.
+                biHeight: -(size.height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let blend = BLENDFUNCTION {
+            BlendOp: AC_SRC_OVER as u8,
+            BlendFlags: 0,
+            SourceConstantAlpha: u8::MAX,
+            AlphaFormat: AC_SRC_ALPHA as u8,
+        };
+
+        unsafe {
+            let screen = GetDC(None);
+            let memory = CreateCompatibleDC(screen);
+            let mut bits = std::ptr::null_mut();
+
+            let result = CreateDIBSection(memory, &info, DIB_RGB_COLORS, &mut bits, None, 0)
+                .and_then(|bitmap| {
+                    let previous = SelectObject(memory, bitmap);
+                    let pixels = std::slice::from_raw_parts_mut(bits as *mut u8, frame.pixels().len());
+
+                    // Layered windows take premultiplied BGRA pixels. Fully transparent pixels
+                    // would let clicks through to the windows beneath, so they're kept barely
+                    // visible instead.
+                    for (target, pixel) in pixels.chunks_mut(4).zip(frame.pixels().chunks(4)) {
+                        let alpha = pixel[3].max(1) as u32;
+                        let [r, g, b] = [0, 1, 2].map(|i| ((pixel[i] as u32 * alpha + 127) / 255) as u8);
+
+                        target.copy_from_slice(&[b, g, r, alpha as u8]);
+                    }
+
+                    let size = SIZE { cx: size.width as i32, cy: size.height as i32 };
+
+                    let result = UpdateLayeredWindow(
+                        hwnd,
+                        screen,
+                        None,
+                        Some(&size),
+                        memory,
+                        Some(&Default::default()),
+                        COLORREF(0),
+                        Some(&blend),
+                        ULW_ALPHA,
+                    );
+
+                    SelectObject(memory, previous);
+                    let _ = DeleteObject(bitmap);
+
+                    result
+                });
+
+            let _ = DeleteDC(memory);
+            ReleaseDC(None, screen);
+
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Converts a color channel from sRGB to linear space.
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a color channel from linear to sRGB space.
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_unorm(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+
+/**
+Draws a frame of the overlay into memory: the dim layer with its cutouts, then every sprite of the
+batch on top of it. Mirrors `overlay.wgsl` and `sprite.wgsl`.
+ */
+pub fn rasterize(
+    size: PhysicalSize<u32>,
+    globals: &Globals,
+    cutouts: &[Cutout],
+    batch: &SpriteBatch,
+    atlas: &GlyphAtlas,
+) -> Frame {
+    let mut raster = Raster::new(size);
+
+    raster.draw_dim_layer(globals, cutouts);
+    raster.draw_sprites(batch, atlas);
+
+    Frame::new(size, raster.pixels)
+}
+
+
+/// The pixels of a frame being drawn, stored like in an sRGB texture.
+struct Raster {
+    size: PhysicalSize<u32>,
+    pixels: Vec<u8>,
+    /// Maps every stored sRGB value to linear space, to blend without calling `powf` each time.
+    linear: [f32; 256],
+}
+
+impl Raster {
+    fn new(size: PhysicalSize<u32>) -> Self {
+        Self {
+            size,
+            pixels: vec![0; (size.width * size.height * 4) as usize],
+            linear: std::array::from_fn(|i| srgb_to_linear(i as f32 / 255.0)),
+        }
+    }
+
+    /// Encodes a linear, straight alpha color the way it's stored.
+    fn encode(color: [f32; 4]) -> [u8; 4] {
+        [
+            to_unorm(linear_to_srgb(color[0])),
+            to_unorm(linear_to_srgb(color[1])),
+            to_unorm(linear_to_srgb(color[2])),
+            to_unorm(color[3]),
+        ]
+    }
+
+    fn store(&mut self, x: u32, y: u32, color: [f32; 4]) {
+        let index = ((y * self.size.width + x) * 4) as usize;
+
+        self.pixels[index..index + 4].copy_from_slice(&Self::encode(color));
+    }
+
+    fn load(&self, x: u32, y: u32) -> [f32; 4] {
+        let index = ((y * self.size.width + x) * 4) as usize;
+        let pixel = &self.pixels[index..index + 4];
+
+        [
+            self.linear[pixel[0] as usize],
+            self.linear[pixel[1] as usize],
+            self.linear[pixel[2] as usize],
+            pixel[3] as f32 / 255.0,
+        ]
+    }
+
+    /// Fills the frame with the dim layer. Pixels far enough from every cutout all have the same
+    /// color, so only the area around the cutouts is shaded one pixel at a time. Those areas are
+    /// merged into spans on every row first, so pixels near overlapping cutouts are shaded once.
+    fn draw_dim_layer(&mut self, globals: &Globals, cutouts: &[Cutout]) {
+        let background = Self::encode(shade(globals, &[], 0.0, 0.0));
+
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&background);
+        }
+
+        let areas = cutouts
+            .iter()
+            .map(|cutout| {
+                // Past four times its distance, the glow is too faint to change any pixel.
+                let glow = if cutout.border > 0.0 { globals.glow * 4.0 } else { 0.0 };
+                let margin = cutout.border + glow + 2.0;
+
+                let [left, top, right, bottom] = cutout.rect;
+
+                [
+                    (left - margin).floor().max(0.0) as u32,
+                    (top - margin).floor().max(0.0) as u32,
+                    ((right + margin).ceil().max(0.0) as u32).min(self.size.width),
+                    ((bottom + margin).ceil().max(0.0) as u32).min(self.size.height),
+                ]
+            })
+            .filter(|[left, top, right, bottom]| left < right && top < bottom)
+            .collect::<Vec<_>>();
+
+        let Some(first) = areas.iter().map(|a| a[1]).min() else {
+            return;
+        };
+
+        let last = areas.iter().map(|a| a[3]).max().unwrap_or(first);
+        let mut spans = Vec::with_capacity(areas.len());
+
+        for y in first..last {
+            spans.clear();
+            spans.extend(
+                areas
+                    .iter()
+                    .filter(|[_, top, _, bottom]| (*top..*bottom).contains(&y))
+                    .map(|[left, _, right, _]| (*left, *right)),
+            );
+            spans.sort_unstable();
+
+            let mut shaded = 0;
+
+            for &(left, right) in &spans {
+                for x in left.max(shaded)..right {
+                    let color = shade(globals, cutouts, x as f32 + 0.5, y as f32 + 0.5);
+
+                    self.store(x, y, color);
+                }
+
+                shaded = shaded.max(right);
+            }
+        }
+    }
+
+    /// Blends every quad of the batch on top of the frame, sampling the atlas like the GPU does.
+    fn draw_sprites(&mut self, batch: &SpriteBatch, atlas: &GlyphAtlas) {
+        let half_width = self.size.width as f32 / 2.0;
+        let half_height = self.size.height as f32 / 2.0;
+        let atlas_size = atlas.size();
+
+        // Every quad is made of four vertices: top-right, top-left, bottom-left and bottom-right.
+        for quad in batch.vertices().chunks_exact(4) {
+            let (top_left, bottom_right) = (quad[1], quad[3]);
+
+            let left = (top_left.position[0] + 1.0) * half_width;
+            let top = (1.0 - top_left.position[1]) * half_height;
+            let right = (bottom_right.position[0] + 1.0) * half_width;
+            let bottom = (1.0 - bottom_right.position[1]) * half_height;
+
+            if right <= left || bottom <= top {
+                continue;
+            }
+
+            // Pixels are covered when their center is inside of the quad.
+            let first = |edge: f32, max: u32| ((edge - 0.5).ceil().max(0.0) as u32).min(max);
+
+            let (x0, x1) = (first(left, self.size.width), first(right, self.size.width));
+            let (y0, y1) = (first(top, self.size.height), first(bottom, self.size.height));
+
+            let color = top_left.color;
+
+            for y in y0..y1 {
+                let t = (y as f32 + 0.5 - top) / (bottom - top);
+                let v = top_left.uv[1] + t * (bottom_right.uv[1] - top_left.uv[1]);
+                let texel_y = ((v * atlas_size.height as f32) as u32).min(atlas_size.height - 1);
+
+                for x in x0..x1 {
+                    let t = (x as f32 + 0.5 - left) / (right - left);
+                    let u = top_left.uv[0] + t * (bottom_right.uv[0] - top_left.uv[0]);
+                    let texel_x = ((u * atlas_size.width as f32) as u32).min(atlas_size.width - 1);
+
+                    let coverage = atlas.pixels()[(texel_y * atlas_size.width + texel_x) as usize] as f32 / 255.0;
+                    let alpha = color[3] * coverage;
+
+                    if alpha <= 0.0 {
+                        continue;
+                    }
+
+                    let below = self.load(x, y);
+
+                    self.store(x, y, [
+                        color[0] * alpha + below[0] * (1.0 - alpha),
+                        color[1] * alpha + below[1] * (1.0 - alpha),
+                        color[2] * alpha + below[2] * (1.0 - alpha),
+                        alpha + below[3] * (1.0 - alpha),
+                    ]);
+                }
+            }
+        }
+    }
+}
+
+
+/// The signed distance from a point to a rounded rectangle: negative inside, positive outside.
+fn rounded_rect(x: f32, y: f32, rect: [f32; 4], radius: f32) -> f32 {
+    let center = [(rect[0] + rect[2]) * 0.5, (rect[1] + rect[3]) * 0.5];
+    let half_size = [(rect[2] - rect[0]) * 0.5, (rect[3] - rect[1]) * 0.5];
+    let r = radius.min(half_size[0].min(half_size[1]));
+    let q = [
+        (x - center[0]).abs() - half_size[0] + r,
+        (y - center[1]).abs() - half_size[1] + r,
+    ];
+
+    q[0].max(0.0).hypot(q[1].max(0.0)) + q[0].max(q[1]).min(0.0) - r
+}
+
+/// Composites a straight alpha color on top of another.
+fn over(top: [f32; 4], bottom: [f32; 4]) -> [f32; 4] {
+    let alpha = top[3] + bottom[3] * (1.0 - top[3]);
+
+    if alpha <= 0.0 {
+        return [0.0; 4];
+    }
+
+    let channel = |i: usize| (top[i] * top[3] + bottom[i] * bottom[3] * (1.0 - top[3])) / alpha;
+
+    [channel(0), channel(1), channel(2), alpha]
+}
+
+/// Computes the color of the dim layer at the given point, like `fs_main` in `overlay.wgsl`.
+fn shade(globals: &Globals, cutouts: &[Cutout], x: f32, y: f32) -> [f32; 4] {
+    let mut distance = 1e9_f32;
+    let mut border = [0.0; 4];
+    let mut glow = [0.0; 4];
+
+    for cutout in cutouts {
+        let d = rounded_rect(x, y, cutout.rect, cutout.radius);
+        let [r, g, b, a] = cutout.color;
+
+        distance = distance.min(d);
+
+        if cutout.border > 0.0 {
+            let ring = (d - cutout.border * 0.5).abs() - cutout.border * 0.5;
+            let coverage = (0.5 - ring).clamp(0.0, 1.0) * a;
+
+            if coverage > border[3] {
+                border = [r, g, b, coverage];
+            }
+
+            if globals.glow > 0.0 && d > cutout.border {
+                let strength = (-3.0 * (d - cutout.border) / globals.glow).exp() * a * 0.5;
+
+                if strength > glow[3] {
+                    glow = [r, g, b, strength];
+                }
+            }
+        }
+    }
+
+    let hole = (0.5 - distance).clamp(0.0, 1.0);
+    let [r, g, b, a] = globals.dim;
+
+    let mut color = [r, g, b, a * (1.0 - hole)];
+
+    color = over([glow[0], glow[1], glow[2], glow[3] * (1.0 - hole)], color);
+    color = over(border, color);
+
+    [color[0], color[1], color[2], color[3] * globals.opacity]
+}
@@ -0,0 +1,49 @@
+// Vertex Shader.
+
+struct VertexInput {
+	@location(0) position: vec2<f32>,
+	@location(1) uv: vec2<f32>,
+	@location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+	@location(1) color: vec4<f32>,
+}
+
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+	var out: VertexOutput;
+
+	out.position = vec4<f32>(model.position, 0.0, 1.0);
+	out.uv = model.uv;
+	out.color = model.color;
+
+	return out;
+}
+
+
+// Fragment Shader.
+
+// Whether the surface expects colors premultiplied by their alpha. Set when creating the pipeline.
+override premultiplied: bool = false;
+
+@group(0) @binding(0)
+var atlas_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var atlas_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+	// The atlas only holds coverage, which scales the alpha of the vertex color.
+	let coverage = textureSample(atlas_texture, atlas_sampler, in.uv).r;
+	let alpha = in.color.a * coverage;
+
+	if premultiplied {
+		return vec4<f32>(in.color.rgb * alpha, alpha);
+	}
+
+	return vec4<f32>(in.color.rgb, alpha);
+}
@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use super::Rect;
+
+
+/// The font used to draw labels on the overlay.
+pub const LABEL_FONT: &str = "C:\\Windows\\Fonts\\segoeuib.ttf";
+
+/// The font labels are drawn with when the one in the theme can't be loaded, such as
+/// [`LABEL_FONT`] outside of Windows. It's DejaVu Sans Bold (see `fonts/LICENSE-DejaVu.txt`), cut
+/// down to the [`ATLAS_CHARACTERS`] and the replacement glyph without hinting, so it stays small.
+const BUILT_IN_FONT: &[u8] = include_bytes!("../fonts/DejaVuSans-Bold-Latin1.ttf");
+
+/// The characters rasterized into the atlas: printable ASCII and the Latin-1 Supplement, which
+/// covers the accented letters of most western languages.
+const ATLAS_CHARACTERS: [std::ops::RangeInclusive<char>; 2] = [' '..='~', '\u{a1}'..='\u{ff}'];
+
+/// The size (in logical pixels) of the labels drawn on the overlay.
+pub const LABEL_SIZE: f32 = 16.0;
+
+/// The width of the glyph atlas texture. Rows are added as needed to fit every glyph.
+const ATLAS_WIDTH: u32 = 512;
+
+/// The space left between glyphs in the atlas to avoid sampling neighbouring glyphs.
+const ATLAS_PADDING: u32 = 1;
+
+/// The size of the opaque block at the top-left corner of the atlas, used to draw solid quads.
+const SOLID_BLOCK: u32 = 3;
+
+
+/// Represents the location of a rasterized character inside of the glyph atlas, alongside the
+/// metrics needed to lay it out.
+#[derive(Debug, Copy, Clone)]
+pub struct Glyph {
+    /// The texture coordinates of the glyph: (left, top, right, bottom).
+    uv: [f32; 4],
+    width: u32,
+    height: u32,
+    /// Horizontal offset from the pen position to the left edge of the bitmap.
+    xmin: i32,
+    /// Vertical offset from the baseline to the bottom edge of the bitmap.
+    ymin: i32,
+    advance: f32,
+}
+
+
+/**
+A single channel texture containing the characters of [`ATLAS_CHARACTERS`] rasterized at a fixed
+size.
+
+Any other character is drawn as the replacement glyph (`�`), or the font's missing glyph box if it
+has none, so it still takes up space. An atlas without a font only contains the solid block, so
+shapes can still be drawn but text can't.
+ */
+pub struct GlyphAtlas {
+    size: PhysicalSize<u32>,
+    pixels: Vec<u8>,
+    glyphs: HashMap<char, Glyph>,
+    ascent: f32,
+    line_height: f32,
+}
+
+impl GlyphAtlas {
+    /// Loads the font at the given path and rasterizes it with the given pixel size.
+    pub fn build(path: &str, px: f32) -> Result<Self, Box<dyn Error>> {
+        let atlas = Self::from_bytes(fs::read(path)?, px)?;
+
+        log::debug!("Glyph atlas of size [{}x{}] created from \"{}\".", atlas.size.width, atlas.size.height, path);
+
+        Ok(atlas)
+    }
+
+    /// Rasterizes the font that comes with the program with the given pixel size. Only when even
+    /// that fails is an atlas without a font returned.
+    pub fn built_in(px: f32) -> Self {
+        match Self::from_bytes(BUILT_IN_FONT, px) {
+            Ok(a) => a,
+            Err(e) => {
+                log::error!("Could not load the built-in label font: {}", e);
+                Self::without_font()
+            }
+        }
+    }
+
+    /// Rasterizes the given font data with the given pixel size.
+    fn from_bytes(data: impl std::ops::Deref<Target = [u8]>, px: f32) -> Result<Self, Box<dyn Error>> {
+        let font = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())?;
+
+        // Glyph 0 is the box fonts draw for the characters they're missing.
+        let replacement = match font.has_glyph(char::REPLACEMENT_CHARACTER) {
+            true => font.rasterize(char::REPLACEMENT_CHARACTER, px),
+            false => font.rasterize_indexed(0, px),
+        };
+
+        let rasterized = ATLAS_CHARACTERS
+            .into_iter()
+            .flatten()
+            .filter(|c| font.has_glyph(*c))
+            .map(|c| (c, font.rasterize(c, px)))
+            .chain([(char::REPLACEMENT_CHARACTER, replacement)])
+            .collect::<Vec<_>>();
+
+        // Glyphs are packed left to right, starting a new row whenever the current one is full.
+        // The first slot is reserved for the solid block.
+        let row_height = rasterized
+            .iter()
+            .map(|(_, (m, _))| m.height as u32)
+            .max()
+            .unwrap_or(0)
+            .max(SOLID_BLOCK) + ATLAS_PADDING;
+
+        let mut placements = Vec::with_capacity(rasterized.len());
+        let mut x = SOLID_BLOCK + ATLAS_PADDING;
+        let mut y = 0;
+
+        for (_, (metrics, _)) in &rasterized {
+            let width = metrics.width as u32;
+
+            if x + width > ATLAS_WIDTH {
+                x = 0;
+                y += row_height;
+            }
+
+            placements.push((x, y));
+            x += width + ATLAS_PADDING;
+        }
+
+        let size = PhysicalSize::new(ATLAS_WIDTH, y + row_height);
+        let mut pixels = vec![0u8; (size.width * size.height) as usize];
+
+        for row in 0..SOLID_BLOCK {
+            let start = (row * size.width) as usize;
+            pixels[start..start + SOLID_BLOCK as usize].fill(u8::MAX);
+        }
+
+        let mut glyphs = HashMap::with_capacity(rasterized.len());
+
+        for ((c, (metrics, bitmap)), (x, y)) in rasterized.into_iter().zip(placements) {
+            for row in 0..metrics.height {
+                let source = row * metrics.width;
+                let target = (y as usize + row) * size.width as usize + x as usize;
+
+                pixels[target..target + metrics.width]
+                    .copy_from_slice(&bitmap[source..source + metrics.width]);
+            }
+
+            glyphs.insert(c, Glyph {
+                uv: [
+                    x as f32 / size.width as f32,
+                    y as f32 / size.height as f32,
+                    (x + metrics.width as u32) as f32 / size.width as f32,
+                    (y + metrics.height as u32) as f32 / size.height as f32,
+                ],
+                width: metrics.width as u32,
+                height: metrics.height as u32,
+                xmin: metrics.xmin,
+                ymin: metrics.ymin,
+                advance: metrics.advance_width,
+            });
+        }
+
+        let (ascent, line_height) = match font.horizontal_line_metrics(px) {
+            Some(m) => (m.ascent, m.new_line_size),
+            None => (px, px),
+        };
+
+        Ok(Self {
+            size,
+            pixels,
+            glyphs,
+            ascent,
+            line_height,
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Creates an atlas that only contains the solid block, for when the font can't be loaded.
+    pub fn without_font() -> Self {
+        let size = PhysicalSize::new(SOLID_BLOCK, SOLID_BLOCK);
+
+        Self {
+            size,
+            pixels: vec![u8::MAX; (size.width * size.height) as usize],
+            glyphs: HashMap::new(),
+            ascent: 0.0,
+            line_height: 0.0,
+        }
+    }
+
+    /// Whether the atlas contains glyphs, meaning text can be drawn with it.
+    pub fn has_font(&self) -> bool {
+        !self.glyphs.is_empty()
+    }
+
+    /// Returns the glyph for the given character, falling back to the replacement glyph.
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Returns the texture coordinates of a fully opaque texel, used to draw solid quads.
+    fn solid_uv(&self) -> [f32; 2] {
+        [
+            (SOLID_BLOCK as f32 / 2.0) / self.size.width as f32,
+            (SOLID_BLOCK as f32 / 2.0) / self.size.height as f32,
+        ]
+    }
+
+    /// Measures the bounding size of a single line of text.
+    pub fn measure(&self, text: &str) -> PhysicalSize<u32> {
+        let width = text.chars().filter_map(|c| self.glyph(c)).map(|g| g.advance).sum::<f32>();
+
+        PhysicalSize::new(width.ceil() as u32, self.line_height.ceil() as u32)
+    }
+}
+
+
+/**
+Defines the input model for a Vertex in the sprite shader.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteVertex {
+    pub(crate) position: [f32; 2],
+    pub(crate) uv: [f32; 2],
+    pub(crate) color: [f32; 4],
+}
+
+impl SpriteVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x4,
+    ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+
+/**
+Collects textured and solid quads (in overlay coordinates) to be drawn in a single draw call.
+
+Every quad is mapped to WebGPU coordinates as it's pushed, which is why the batch needs to know the
+area of the overlay it's drawn on (the viewport).
+ */
+pub struct SpriteBatch<'a> {
+    atlas: &'a GlyphAtlas,
+    viewport: Rect,
+    vertices: Vec<SpriteVertex>,
+    indices: Vec<u32>,
+    /// Multiplies the alpha of every quad pushed from then on.
+    opacity: f32,
+}
+
+impl<'a> SpriteBatch<'a> {
+    pub fn new(atlas: &'a GlyphAtlas, viewport: Rect) -> Self {
+        Self {
+            atlas,
+            viewport,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            opacity: 1.0,
+        }
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    pub fn vertices(&self) -> &[SpriteVertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Pushes a quad covering the given rect in overlay coordinates.
+    fn push_quad(&mut self, left: f32, top: f32, right: f32, bottom: f32, uv: [f32; 4], color: [f32; 4]) {
+        let center_x = self.viewport.width() as f32 / 2.0;
+        let center_y = self.viewport.height() as f32 / 2.0;
+
+        let (left, top, right, bottom) = (
+            left - self.viewport.left as f32,
+            top - self.viewport.top as f32,
+            right - self.viewport.left as f32,
+            bottom - self.viewport.top as f32,
+        );
+
+        let (left, top, right, bottom) = (
+            (left - center_x) / center_x,
+            -(top - center_y) / center_y,
+            (right - center_x) / center_x,
+            -(bottom - center_y) / center_y,
+        );
+
+        let offset = self.vertices.len() as u32;
+        let color = [color[0], color[1], color[2], color[3] * self.opacity];
+
+        // The initial vertex is the top-right point, same as with the clipping quads.
+        self.vertices.extend([
+            SpriteVertex { position: [right, top], uv: [uv[2], uv[1]], color },
+            SpriteVertex { position: [left, top], uv: [uv[0], uv[1]], color },
+            SpriteVertex { position: [left, bottom], uv: [uv[0], uv[3]], color },
+            SpriteVertex { position: [right, bottom], uv: [uv[2], uv[3]], color },
+        ]);
+        self.indices.extend([0, 1, 2, 0, 2, 3].map(|i| i + offset));
+    }
+
+    /// Pushes a solid colored rect.
+    pub fn push_rect(&mut self, rect: Rect, color: [f32; 4]) {
+        let [u, v] = self.atlas.solid_uv();
+
+        self.push_quad(
+            rect.left as f32,
+            rect.top as f32,
+            rect.right as f32,
+            rect.bottom as f32,
+            [u, v, u, v],
+            color,
+        );
+    }
+
+    /// Pushes the outline of a rect, drawn inwards with the given thickness.
+    pub fn push_outline(&mut self, rect: Rect, thickness: i32, color: [f32; 4]) {
+        let (left, top, right, bottom) = rect.raw();
+
+        self.push_rect(Rect { left, top, right, bottom: top + thickness }, color);
+        self.push_rect(Rect { left, top: bottom - thickness, right, bottom }, color);
+        self.push_rect(Rect { left, top: top + thickness, right: left + thickness, bottom: bottom - thickness }, color);
+        self.push_rect(Rect { left: right - thickness, top: top + thickness, right, bottom: bottom - thickness }, color);
+    }
+
+    /// Pushes a single line of text, where the given position is the top-left corner of the line.
+    pub fn push_text(&mut self, position: PhysicalPosition<i32>, text: &str, color: [f32; 4]) {
+        let baseline = position.y as f32 + self.atlas.ascent.round();
+        let mut pen = position.x as f32;
+
+        for glyph in text.chars().filter_map(|c| self.atlas.glyph(c).copied()) {
+            if glyph.width > 0 && glyph.height > 0 {
+                let left = (pen + glyph.xmin as f32).round();
+                let bottom = baseline - glyph.ymin as f32;
+
+                self.push_quad(
+                    left,
+                    bottom - glyph.height as f32,
+                    left + glyph.width as f32,
+                    bottom,
+                    glyph.uv,
+                    color,
+                );
+            }
+
+            pen += glyph.advance;
+        }
+    }
+
+    /// Pushes several lines of text on top of a solid background, with the top-left corner of the
+    /// background at the given position. The panel is kept inside of the viewport it's opened in.
+    ///
+    /// Nothing is pushed if the atlas has no font, since the panel couldn't be read.
+    pub fn push_panel(
+        &mut self,
+        position: PhysicalPosition<i32>,
+        lines: &[String],
+        padding: i32,
+        background: [f32; 4],
+        foreground: [f32; 4],
+    ) {
+        if !self.atlas.has_font() {
+            return;
+        }
+
+        let line_height = self.atlas.measure("").height as i32;
+        let width = lines
+            .iter()
+            .map(|l| self.atlas.measure(l).width as i32)
+            .max()
+            .unwrap_or(0) + padding * 2;
+        let height = line_height * lines.len() as i32 + padding * 2;
+
+        // Panels belonging to another viewport are left where they are, whatever overlaps this one
+        // still has to be drawn.
+        let (left, top) = match self.viewport.contains(position.x, position.y) {
+            true => (
+                position.x.min(self.viewport.right - width).max(self.viewport.left),
+                position.y.min(self.viewport.bottom - height).max(self.viewport.top),
+            ),
+            false => (position.x, position.y),
+        };
+
+        self.push_rect(Rect::new(left, top, width as u32, height as u32), background);
+
+        for (index, line) in lines.iter().enumerate() {
+            let position = PhysicalPosition::new(left + padding, top + padding + line_height * index as i32);
+
+            self.push_text(position, line, foreground);
+        }
+    }
+
+    /// Pushes a text label on top of a solid background, centered on the given point.
+    ///
+    /// Nothing is pushed if the atlas has no font, since the label couldn't be read.
+    pub fn push_label(
+        &mut self,
+        center: PhysicalPosition<i32>,
+        text: &str,
+        padding: i32,
+        background: [f32; 4],
+        foreground: [f32; 4],
+    ) {
+        if !self.atlas.has_font() {
+            return;
+        }
+
+        let size = self.atlas.measure(text);
+        let left = center.x - (size.width as i32 / 2);
+        let top = center.y - (size.height as i32 / 2);
+
+        self.push_rect(
+            Rect {
+                left: left - padding,
+                top: top - padding,
+                right: left + size.width as i32 + padding,
+                bottom: top + size.height as i32 + padding,
+            },
+            background,
+        );
+        self.push_text(PhysicalPosition::new(left, top), text, foreground);
+    }
+}
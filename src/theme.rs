@@ -0,0 +1,281 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use super::{Preferences, LABEL_FONT, LABEL_SIZE};
+
+
+/// The theme built from the user preferences, once it's first needed.
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+
+/// The colors that can be referred to by name, as RGB values.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("black", 0x000000),
+    ("white", 0xFFFFFF),
+    ("gray", 0x808080),
+    ("grey", 0x808080),
+    ("silver", 0xC0C0C0),
+    ("red", 0xFF0000),
+    ("orange", 0xFFA500),
+    ("yellow", 0xFFFF00),
+    ("gold", 0xFFD700),
+    ("lime", 0x00FF00),
+    ("green", 0x008000),
+    ("cyan", 0x00FFFF),
+    ("blue", 0x0000FF),
+    ("dodgerblue", 0x1E90FF),
+    ("magenta", 0xFF00FF),
+    ("purple", 0x800080),
+    ("pink", 0xFFC0CB),
+];
+
+
+/**
+A color with straight (non premultiplied) alpha, where every channel ranges from 0 to 1.
+
+Colors are written in the preferences as `#RGB`, `#RRGGBB`, `#RRGGBBAA`, `rgb(r, g, b)`,
+`rgba(r, g, b, a)` or by name (e.g. `white`). In the functional notations, the color channels
+range from 0 to 255 and the alpha from 0 to 1.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Color(pub [f32; 4]);
+
+impl Color {
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self([r, g, b, a])
+    }
+
+    /// Creates an opaque color from a `0xRRGGBB` value.
+    pub fn from_rgb(rgb: u32) -> Self {
+        Self([
+            ((rgb >> 16) & 0xFF) as f32 / 255.0,
+            ((rgb >> 8) & 0xFF) as f32 / 255.0,
+            (rgb & 0xFF) as f32 / 255.0,
+            1.0,
+        ])
+    }
+
+    /// Returns the same color with its alpha multiplied by the given factor.
+    pub fn fade(self, factor: f32) -> Self {
+        let [r, g, b, a] = self.0;
+
+        Self([r, g, b, a * factor])
+    }
+
+    fn parse_hex(hex: &str) -> Option<Self> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let value = u32::from_str_radix(hex, 16).ok()?;
+
+        match hex.len() {
+            3 => {
+                // Every digit of the short form is doubled, so `#f80` is `#ff8800`.
+                let [r, g, b] = [8, 4, 0].map(|shift| ((value >> shift) & 0xF) * 0x11);
+
+                Some(Self::from_rgb((r << 16) | (g << 8) | b))
+            }
+            6 => Some(Self::from_rgb(value)),
+            8 => Some(Self::from_rgb(value >> 8).fade((value & 0xFF) as f32 / 255.0)),
+            _ => None,
+        }
+    }
+
+    fn parse_function(name: &str, arguments: &str) -> Option<Self> {
+        let arguments = arguments
+            .split(',')
+            .map(|a| a.trim().parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        let channel = |v: f32| (0.0..=255.0).contains(&v).then_some(v / 255.0);
+
+        match (name, arguments.as_slice()) {
+            ("rgb", [r, g, b]) => Some(Self([channel(*r)?, channel(*g)?, channel(*b)?, 1.0])),
+            ("rgba", [r, g, b, a]) if (0.0..=1.0).contains(a) => {
+                Some(Self([channel(*r)?, channel(*g)?, channel(*b)?, *a]))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim().to_lowercase();
+
+        let color = if let Some(hex) = value.strip_prefix('#') {
+            Self::parse_hex(hex)
+        } else if let Some((name, rest)) = value.split_once('(') {
+            rest.strip_suffix(')').and_then(|a| Self::parse_function(name.trim(), a))
+        } else if value == "transparent" {
+            Some(Self::rgba(0.0, 0.0, 0.0, 0.0))
+        } else {
+            NAMED_COLORS
+                .iter()
+                .find(|(name, _)| *name == value)
+                .map(|(_, rgb)| Self::from_rgb(*rgb))
+        };
+
+        color.ok_or_else(|| format!("invalid color \"{s}\""))
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+
+/// The themes that come with the program, which the user's theme is based on.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    /// Thick, opaque borders and black on yellow labels, for low vision users.
+    HighContrast,
+    /// Square corners without borders nor glow, like the overlay used to look.
+    Minimal,
+}
+
+
+/**
+Represents the theme block of the preferences: a built-in theme, and every entry of it the user
+wants to change. Entries that are missing are taken from the built-in theme.
+ */
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct ThemePreferences {
+    pub name: ThemeName,
+    pub border_color: Option<Color>,
+    pub border_width: Option<u32>,
+    pub glow: Option<u32>,
+    pub corner_radius: Option<u32>,
+    pub candidate_color: Option<Color>,
+    pub hovered_color: Option<Color>,
+    pub selected_color: Option<Color>,
+    pub centered_color: Option<Color>,
+    pub font: Option<String>,
+    pub font_size: Option<f32>,
+    pub label_color: Option<Color>,
+    pub label_background: Option<Color>,
+    pub text_color: Option<Color>,
+    pub panel_color: Option<Color>,
+}
+
+
+/**
+The colors, sizes and font the overlay is drawn with. Sizes are in logical pixels.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// The border around the hovered window.
+    pub border_color: Color,
+    /// The width of the border around the hovered and selected windows. Zero disables borders.
+    pub border_width: u32,
+    /// How far the glow around the hovered and selected windows spreads. Zero disables it.
+    pub glow: u32,
+    /// The radius of the corners of the windows cut out of the overlay.
+    pub corner_radius: u32,
+    /// The outline of every window that can be centered.
+    pub candidate_color: Color,
+    /// The preview of where the hovered window would be centered.
+    pub hovered_color: Color,
+    /// The border around the selected windows and search matches.
+    pub selected_color: Color,
+    /// The outline of windows that are already centered, and of the guides they line up with.
+    pub centered_color: Color,
+    /// The path of the font every label and panel is drawn with.
+    pub font: String,
+    pub font_size: f32,
+    /// The text and background of the hints and the best search match.
+    pub label_color: Color,
+    pub label_background: Color,
+    /// The text and background of the menu, panels and the search bar.
+    pub text_color: Color,
+    pub panel_color: Color,
+}
+
+impl Theme {
+    /// Gets the theme defined by the global user preferences.
+    pub fn get() -> &'static Self {
+        THEME.get_or_init(|| Self::from_preferences(&Preferences::get().theme))
+    }
+
+    /// Returns one of the themes that come with the program.
+    pub fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Self {
+                border_color: Color::rgba(1.0, 1.0, 1.0, 1.0),
+                border_width: 2,
+                glow: 12,
+                corner_radius: 8,
+                candidate_color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+                hovered_color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+                selected_color: Color::rgba(0.37, 0.67, 1.0, 1.0),
+                centered_color: Color::rgba(0.4, 0.9, 0.5, 0.9),
+                font: LABEL_FONT.to_string(),
+                font_size: LABEL_SIZE,
+                label_color: Color::rgba(0.0, 0.0, 0.0, 1.0),
+                label_background: Color::rgba(1.0, 0.84, 0.37, 1.0),
+                text_color: Color::rgba(1.0, 1.0, 1.0, 1.0),
+                panel_color: Color::rgba(0.12, 0.12, 0.12, 0.95),
+            },
+
+            ThemeName::HighContrast => Self {
+                border_color: Color::from_rgb(0xFFFF00),
+                border_width: 4,
+                glow: 0,
+                corner_radius: 8,
+                candidate_color: Color::from_rgb(0xFFFFFF),
+                hovered_color: Color::from_rgb(0xFFFF00),
+                selected_color: Color::from_rgb(0x00FFFF),
+                centered_color: Color::from_rgb(0x00FF00),
+                font: LABEL_FONT.to_string(),
+                font_size: LABEL_SIZE * 1.25,
+                label_color: Color::from_rgb(0x000000),
+                label_background: Color::from_rgb(0xFFFF00),
+                text_color: Color::from_rgb(0xFFFFFF),
+                panel_color: Color::from_rgb(0x000000),
+            },
+
+            ThemeName::Minimal => Self {
+                border_width: 0,
+                glow: 0,
+                corner_radius: 0,
+                ..Self::builtin(ThemeName::Default)
+            },
+        }
+    }
+
+    /// Builds the theme described by the theme block of the preferences.
+    pub fn from_preferences(preferences: &ThemePreferences) -> Self {
+        let base = Self::builtin(preferences.name);
+
+        Self {
+            border_color: preferences.border_color.unwrap_or(base.border_color),
+            border_width: preferences.border_width.unwrap_or(base.border_width),
+            glow: preferences.glow.unwrap_or(base.glow),
+            corner_radius: preferences.corner_radius.unwrap_or(base.corner_radius),
+            candidate_color: preferences.candidate_color.unwrap_or(base.candidate_color),
+            hovered_color: preferences.hovered_color.unwrap_or(base.hovered_color),
+            selected_color: preferences.selected_color.unwrap_or(base.selected_color),
+            centered_color: preferences.centered_color.unwrap_or(base.centered_color),
+            font: preferences.font.clone().unwrap_or(base.font),
+            font_size: preferences.font_size.unwrap_or(base.font_size),
+            label_color: preferences.label_color.unwrap_or(base.label_color),
+            label_background: preferences.label_background.unwrap_or(base.label_background),
+            text_color: preferences.text_color.unwrap_or(base.text_color),
+            panel_color: preferences.panel_color.unwrap_or(base.panel_color),
+        }
+    }
+}
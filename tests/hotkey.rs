@@ -0,0 +1,43 @@
+//! Tests for the hotkeys written in the preferences.
+
+use centered_windows::{Hotkey, HotkeyKey};
+
+
+fn parse(hotkey: &str) -> Hotkey {
+    hotkey.parse().unwrap_or_else(|e| panic!("\"{hotkey}\" should be a valid hotkey: {e}"))
+}
+
+#[test]
+fn modifiers_and_keys_are_parsed() {
+    let hotkey = parse("Ctrl+Alt+C");
+
+    assert!(hotkey.ctrl && hotkey.alt);
+    assert!(!hotkey.shift && !hotkey.super_key);
+    assert_eq!(hotkey.key, HotkeyKey::Letter('C'));
+}
+
+#[test]
+fn names_are_not_case_sensitive_and_may_be_spaced() {
+    assert_eq!(parse("ctrl + SHIFT + pgup"), parse("Ctrl+Shift+PageUp"));
+    assert_eq!(parse("Win+1"), parse("Super+1"));
+}
+
+#[test]
+fn function_keys_need_no_modifier() {
+    assert_eq!(parse("F12").key, HotkeyKey::Function(12));
+    assert!("F25".parse::<Hotkey>().is_err());
+}
+
+#[test]
+fn invalid_hotkeys_are_rejected() {
+    for hotkey in ["", "C", "Ctrl+", "Ctrl+Ctrl+C", "Hyper+C", "Ctrl+Alt+Nope"] {
+        assert!(hotkey.parse::<Hotkey>().is_err(), "\"{hotkey}\" should be rejected");
+    }
+}
+
+#[test]
+fn hotkeys_are_written_back_the_same_way() {
+    for hotkey in ["Ctrl+Alt+C", "Shift+Super+F4", "Alt+Space"] {
+        assert_eq!(parse(hotkey).to_string(), hotkey);
+    }
+}
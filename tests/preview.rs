@@ -0,0 +1,206 @@
+//! Pixel tests for the overlay, rendered offscreen from the desktops in `tests/fixtures`.
+//!
+//! The golden images in `tests/golden` are written again when the `UPDATE_GOLDEN` environment
+//! variable is set. Tests rendering with the GPU are skipped when no adapter is available, while
+//! the software renderer has to match the same golden images.
+
+use std::fs;
+
+use centered_windows::{Desktop, Frame, OverlayApp, RendererKind, Theme, ThemeName};
+use winit::dpi::PhysicalPosition;
+
+
+/// The alpha of the dim layer, as set by the default `overlay_opacity` of 0.6.
+const DIM_ALPHA: u8 = 153;
+
+/// How much a channel may differ from the golden image, to allow for differences between adapters.
+const TOLERANCE: u8 = 2;
+
+
+/// Renders a fixture desktop with the GPU, with the cursor at the given desktop coordinates.
+fn render(fixture: &str, x: i32, y: i32) -> Option<Frame> {
+    render_with(RendererKind::Gpu, fixture, x, y)
+}
+
+/// Renders a fixture desktop with the given renderer. Labels are left out, so that frames don't
+/// depend on the fonts installed on the machine.
+fn render_with(renderer: RendererKind, fixture: &str, x: i32, y: i32) -> Option<Frame> {
+    let desktop = Desktop::try_from_file(&format!("tests/fixtures/{fixture}.yml"))
+        .expect("The fixture should be a valid desktop");
+
+    let theme = Theme {
+        font: String::new(),
+        ..Theme::builtin(ThemeName::Default)
+    };
+
+    match OverlayApp::render_preview(&desktop, PhysicalPosition::new(x, y), &theme, renderer) {
+        Ok(frame) => Some(frame),
+        Err(e) => {
+            eprintln!("Skipping, the overlay could not be rendered offscreen: {e}");
+            None
+        }
+    }
+}
+
+fn assert_transparent(frame: &Frame, x: u32, y: u32) {
+    assert_eq!(frame.pixel(x, y)[3], 0, "({x}, {y}) should be cut out of the overlay");
+}
+
+fn assert_dimmed(frame: &Frame, x: u32, y: u32) {
+    let [r, g, b, a] = frame.pixel(x, y);
+
+    assert_eq!([r, g, b], [0, 0, 0], "({x}, {y}) should be black");
+    assert!(a.abs_diff(DIM_ALPHA) <= TOLERANCE, "({x}, {y}) should be dimmed, its alpha is {a}");
+}
+
+/// Renders a fixture desktop on the CPU, which always works.
+fn render_software(fixture: &str, x: i32, y: i32) -> Frame {
+    render_with(RendererKind::Software, fixture, x, y).expect("The software renderer should always work")
+}
+
+fn assert_matches_golden(name: &str, frame: &Frame) {
+    let path = format!("tests/golden/{name}.png");
+
+    // The golden images are only written from frames rendered with the GPU.
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        frame.save_png(&path).unwrap();
+        return;
+    }
+
+    assert_same_as_golden(&path, frame);
+}
+
+fn assert_same_as_golden(path: &str, frame: &Frame) {
+    let golden = Frame::from_png(&fs::read(path).unwrap()).unwrap();
+
+    assert_eq!(golden.size(), frame.size());
+
+    let differing = golden.pixels()
+        .chunks(4)
+        .zip(frame.pixels().chunks(4))
+        .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > TOLERANCE))
+        .count();
+
+    // A few pixels along anti-aliased edges may be rounded differently.
+    assert!(differing <= golden.pixels().len() / 4 / 1000, "{differing} pixels differ from {path}");
+}
+
+
+#[test]
+fn hovered_window_is_cut_out() {
+    let Some(frame) = render("single", 500, 400) else {
+        return;
+    };
+
+    assert_transparent(&frame, 300, 150);
+    assert_transparent(&frame, 100, 400);
+    assert_transparent(&frame, 899, 150);
+    assert_transparent(&frame, 500, 699);
+
+    // Corners are rounded, so the very corner of the window stays dimmed.
+    assert!(frame.pixel(899, 699)[3] > 0);
+
+    // The border is drawn right outside of the window.
+    assert_eq!(frame.pixel(99, 400), [255, 255, 255, 255]);
+
+    assert_dimmed(&frame, 10, 10);
+    assert_dimmed(&frame, 1900, 1060);
+}
+
+#[test]
+fn cutouts_are_not_flipped() {
+    let Some(frame) = render("single", 500, 400) else {
+        return;
+    };
+
+    // The window is near the top of the monitor, so the same spot near the bottom is dimmed.
+    assert_transparent(&frame, 300, 150);
+    assert_dimmed(&frame, 300, 1080 - 150);
+    assert_dimmed(&frame, 1920 - 300, 150);
+}
+
+#[test]
+fn preview_is_drawn_where_the_window_would_be_centered() {
+    let Some(frame) = render("single", 500, 400) else {
+        return;
+    };
+
+    // The window would be centered on the work area at (560, 220), sized 800x600.
+    let [_, _, _, preview] = frame.pixel(1300, 800);
+    let [_, _, _, outside] = frame.pixel(1300, 900);
+
+    assert!(preview > outside, "the preview should be drawn on top of the dim layer");
+    assert_dimmed(&frame, 1300, 900);
+}
+
+#[test]
+fn nothing_is_cut_out_without_a_hovered_window() {
+    let Some(frame) = render("single", 1500, 900) else {
+        return;
+    };
+
+    assert_dimmed(&frame, 300, 150);
+    assert_dimmed(&frame, 1500, 900);
+}
+
+#[test]
+fn odd_sizes_keep_every_row_aligned() {
+    let Some(frame) = render("odd", 800, 600) else {
+        return;
+    };
+
+    assert_eq!((frame.size().width, frame.size().height), (1023, 767));
+
+    assert_transparent(&frame, 1022, 600);
+    assert_transparent(&frame, 800, 766);
+    assert_transparent(&frame, 623, 600);
+    assert_transparent(&frame, 800, 467);
+    assert_dimmed(&frame, 10, 600);
+    assert_dimmed(&frame, 1022, 10);
+}
+
+#[test]
+fn negative_monitor_origins_are_offset() {
+    let Some(frame) = render("negative", -700, 250) else {
+        return;
+    };
+
+    // The desktop starts at (-1280, -200), so the window is drawn at (280, 200) in the frame.
+    assert_eq!((frame.size().width, frame.size().height), (3200, 1280));
+
+    assert_transparent(&frame, 300, 220);
+    assert_transparent(&frame, 860, 220);
+    assert_dimmed(&frame, 1280 + 300, 220);
+    assert_dimmed(&frame, 300, 1280 - 220);
+}
+
+#[test]
+fn single_monitor_matches_golden() {
+    if let Some(frame) = render("single", 500, 400) {
+        assert_matches_golden("single", &frame);
+    }
+}
+
+#[test]
+fn negative_monitor_origins_match_golden() {
+    if let Some(frame) = render("negative", 1500, 700) {
+        assert_matches_golden("negative", &frame);
+    }
+}
+
+#[test]
+fn software_renderer_cuts_out_the_hovered_window() {
+    let frame = render_software("single", 500, 400);
+
+    assert_transparent(&frame, 300, 150);
+    assert_transparent(&frame, 899, 150);
+    assert_eq!(frame.pixel(99, 400), [255, 255, 255, 255]);
+    assert_dimmed(&frame, 10, 10);
+    assert_dimmed(&frame, 300, 1080 - 150);
+}
+
+#[test]
+fn software_renderer_matches_golden() {
+    assert_same_as_golden("tests/golden/single.png", &render_software("single", 500, 400));
+    assert_same_as_golden("tests/golden/negative.png", &render_software("negative", 1500, 700));
+}
@@ -0,0 +1,69 @@
+//! Tests for bringing back off-screen windows, on the desktops in `tests/fixtures`.
+//!
+//! Desktop windows don't exist, so they're only ever rescued on a dry run.
+
+use centered_windows::{rescue_windows, Desktop, Rect, WindowMove};
+
+
+/// Rescues the windows of the fixture with less than the given fraction of them visible, centering
+/// them on the work area of their monitor.
+fn rescue(fixture: &str, threshold: f64) -> Vec<WindowMove> {
+    let mut desktop = Desktop::try_from_file(&format!("tests/fixtures/{fixture}.yml"))
+        .expect("The fixture should be a valid desktop");
+
+    desktop.use_absolute_area = Some(false);
+
+    let mut windows = desktop.windows();
+
+    rescue_windows(&mut windows, &desktop.monitors(), threshold, true)
+        .into_iter()
+        .map(|r| r.expect("Nothing is moved on a dry run, so nothing can fail"))
+        .collect()
+}
+
+fn titles(rescues: Vec<WindowMove>) -> Vec<String> {
+    rescues.into_iter().map(|r| r.title).collect()
+}
+
+/// Finds where the window with the given title would be moved to.
+fn target(rescues: &[WindowMove], title: &str) -> Rect {
+    rescues
+        .iter()
+        .find(|r| r.title == title)
+        .unwrap_or_else(|| panic!("\"{title}\" should be rescued"))
+        .to
+}
+
+#[test]
+fn only_windows_mostly_off_screen_are_rescued() {
+    assert_eq!(titles(rescue("offscreen", 0.2)), ["Gone", "Barely visible", "Too big"]);
+}
+
+#[test]
+fn higher_thresholds_rescue_more_windows() {
+    let rescued = titles(rescue("offscreen", 0.6));
+
+    assert_eq!(rescued, ["Gone", "Barely visible", "Mostly visible", "Too big"]);
+}
+
+#[test]
+fn rescued_windows_are_centered_on_the_work_area() {
+    let rescues = rescue("offscreen", 0.2);
+
+    assert_eq!(target(&rescues, "Gone"), Rect::new(560, 220, 800, 600));
+}
+
+#[test]
+fn rescued_windows_are_shrunk_to_fit() {
+    let rescues = rescue("offscreen", 0.2);
+
+    assert_eq!(target(&rescues, "Too big"), Rect::new(0, 0, 1920, 1040));
+}
+
+#[test]
+fn rescues_remember_where_windows_were() {
+    let rescues = rescue("offscreen", 0.2);
+    let gone = rescues.iter().find(|r| r.title == "Gone").unwrap();
+
+    assert_eq!(gone.from, Rect::new(2200, 200, 800, 600));
+}
@@ -0,0 +1,38 @@
+//! Tests for the glyphs and sprites labels are drawn with.
+
+use centered_windows::{GlyphAtlas, Rect, SpriteBatch};
+
+
+#[test]
+fn the_built_in_font_can_draw_text() {
+    let atlas = GlyphAtlas::built_in(16.0);
+
+    assert!(atlas.has_font());
+    assert!(atlas.measure("Notepad").width > 0);
+}
+
+#[test]
+fn missing_characters_are_drawn_as_the_replacement_glyph() {
+    let atlas = GlyphAtlas::built_in(16.0);
+    let replacement = atlas.measure("\u{fffd}").width;
+
+    assert!(replacement > 0);
+    assert_eq!(atlas.measure("日本").width, replacement * 2);
+    assert!(atlas.measure("Café").width > atlas.measure("Caf").width);
+}
+
+#[test]
+fn batches_can_hold_more_vertices_than_fit_in_16_bits() {
+    let atlas = GlyphAtlas::without_font();
+    let mut batch = SpriteBatch::new(&atlas, Rect::new(0, 0, 1920, 1080));
+
+    // Every rect takes four vertices.
+    for i in 0..20_000 {
+        batch.push_rect(Rect::new(i % 1920, i / 1920, 1, 1), [1.0; 4]);
+    }
+
+    let last = *batch.indices().iter().max().unwrap();
+
+    assert_eq!(last as usize, batch.vertices().len() - 1);
+    assert!(last > u16::MAX as u32);
+}
@@ -0,0 +1,43 @@
+//! Tests for the colors written in the theme preferences.
+
+use centered_windows::Color;
+
+
+fn parse(color: &str) -> Color {
+    color.parse().unwrap_or_else(|e| panic!("\"{color}\" should be a valid color: {e}"))
+}
+
+#[test]
+fn short_hex_colors_double_every_digit() {
+    assert_eq!(parse("#f80"), Color::from_rgb(0xFF8800));
+    assert_eq!(parse("#FFF"), Color::from_rgb(0xFFFFFF));
+    assert_eq!(parse("#000"), Color::from_rgb(0x000000));
+}
+
+#[test]
+fn hex_colors_are_opaque_without_alpha() {
+    assert_eq!(parse("#1e90ff"), Color::from_rgb(0x1E90FF));
+    assert_eq!(parse("#1E90FF").0[3], 1.0);
+}
+
+#[test]
+fn hex_colors_may_have_alpha() {
+    let color = parse("#ff000080");
+
+    assert_eq!(color, Color::from_rgb(0xFF0000).fade(128.0 / 255.0));
+    assert_eq!(parse("#00ff0000").0[3], 0.0);
+}
+
+#[test]
+fn hex_colors_of_the_wrong_length_are_rejected() {
+    for color in ["#", "#f", "#ff", "#ffff", "#fffff", "#fffffff", "#fffffffff"] {
+        assert!(color.parse::<Color>().is_err(), "\"{color}\" should be rejected");
+    }
+}
+
+#[test]
+fn hex_colors_with_other_digits_are_rejected() {
+    for color in ["#ggg", "#12345z", "#+12345", "#-fff", "# fff", "#ff 00 00"] {
+        assert!(color.parse::<Color>().is_err(), "\"{color}\" should be rejected");
+    }
+}